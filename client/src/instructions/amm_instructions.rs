@@ -9,7 +9,10 @@ use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysva
 use raydium_cp_swap::accounts as raydium_cp_accounts;
 use raydium_cp_swap::instruction as raydium_cp_instructions;
 use raydium_cp_swap::{
-    states::{AMM_CONFIG_SEED, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED},
+    states::{
+        AMM_CONFIG_SEED, GLOBAL_CONFIG_SEED, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED,
+        POOL_VAULT_SEED,
+    },
     AUTH_SEED,
 };
 use std::rc::Rc;
@@ -137,6 +140,8 @@ pub fn initialize_pool_instr(
 pub fn deposit_instr(
     config: &ClientConfig,
     pool_id: Pubkey,
+    amm_config: Pubkey,
+    observation_account: Pubkey,
     token_0_mint: Pubkey,
     token_1_mint: Pubkey,
     token_lp_mint: Pubkey,
@@ -156,6 +161,8 @@ pub fn deposit_instr(
     let program = client.program(config.raydium_cp_program)?;
 
     let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program.id());
+    let (global_config, __bump) =
+        Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED.as_bytes()], &program.id());
 
     let instructions = program
         .request()
@@ -173,6 +180,9 @@ pub fn deposit_instr(
             vault_0_mint: token_0_mint,
             vault_1_mint: token_1_mint,
             lp_mint: token_lp_mint,
+            observation_state: observation_account,
+            amm_config,
+            global_config,
         })
         .args(raydium_cp_instructions::Deposit {
             lp_token_amount,
@@ -186,6 +196,8 @@ pub fn deposit_instr(
 pub fn withdraw_instr(
     config: &ClientConfig,
     pool_id: Pubkey,
+    amm_config: Pubkey,
+    observation_account: Pubkey,
     token_0_mint: Pubkey,
     token_1_mint: Pubkey,
     token_lp_mint: Pubkey,
@@ -205,6 +217,8 @@ pub fn withdraw_instr(
     let program = client.program(config.raydium_cp_program)?;
 
     let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program.id());
+    let (global_config, __bump) =
+        Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED.as_bytes()], &program.id());
 
     let instructions = program
         .request()
@@ -223,6 +237,9 @@ pub fn withdraw_instr(
             vault_1_mint: token_1_mint,
             lp_mint: token_lp_mint,
             memo_program: spl_memo::id(),
+            observation_state: observation_account,
+            amm_config,
+            global_config,
         })
         .args(raydium_cp_instructions::Withdraw {
             lp_token_amount,
@@ -256,6 +273,8 @@ pub fn swap_base_input_instr(
     let program = client.program(config.raydium_cp_program)?;
 
     let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program.id());
+    let (global_config, __bump) =
+        Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED.as_bytes()], &program.id());
 
     let instructions = program
         .request()
@@ -273,6 +292,7 @@ pub fn swap_base_input_instr(
             input_token_mint,
             output_token_mint,
             observation_state: observation_account,
+            global_config,
         })
         .args(raydium_cp_instructions::SwapBaseInput {
             amount_in,
@@ -305,6 +325,8 @@ pub fn swap_base_output_instr(
     let program = client.program(config.raydium_cp_program)?;
 
     let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program.id());
+    let (global_config, __bump) =
+        Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED.as_bytes()], &program.id());
 
     let instructions = program
         .request()
@@ -322,6 +344,7 @@ pub fn swap_base_output_instr(
             input_token_mint,
             output_token_mint,
             observation_state: observation_account,
+            global_config,
         })
         .args(raydium_cp_instructions::SwapBaseOutput {
             max_amount_in,
@@ -338,14 +338,12 @@ pub fn handle_program_instruction(
             let ix = decode_instruction::<instruction::UpdateAmmConfig>(&mut ix_data).unwrap();
             #[derive(Debug)]
             pub struct UpdateAmmConfig {
-                pub param: u8,
-                pub value: u64,
+                pub update: AmmConfigUpdate,
             }
             impl From<instruction::UpdateAmmConfig> for UpdateAmmConfig {
                 fn from(instr: instruction::UpdateAmmConfig) -> UpdateAmmConfig {
                     UpdateAmmConfig {
-                        param: instr.param,
-                        value: instr.value,
+                        update: instr.update,
                     }
                 }
             }
@@ -11,6 +11,7 @@ use bytemuck::Pod;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{account::Account as CliAccount, pubkey::Pubkey};
 use std::ops::Mul;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn deserialize_anchor_account<T: AccountDeserialize>(account: &CliAccount) -> Result<T> {
     let mut data: &[u8] = &account.data;
@@ -42,6 +43,15 @@ pub fn amount_with_slippage(amount: u64, slippage: f64, round_up: bool) -> u64 {
     }
 }
 
+/// Local estimate of the on-chain unix timestamp, used to price stable swap pools
+/// whose amplification coefficient ramps over time
+pub fn block_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 pub fn get_pool_mints_inverse_fee(
     rpc_client: &RpcClient,
     token_mint_0: Pubkey,
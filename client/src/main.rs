@@ -241,6 +241,7 @@ fn main() -> Result<()> {
                 .unwrap();
             // calculate amount
             let results = raydium_cp_swap::curve::CurveCalculator::lp_tokens_to_trading_tokens(
+                pool_state.curve(block_timestamp()).unwrap().as_ref(),
                 u128::from(lp_token_amount),
                 u128::from(pool_state.lp_supply),
                 u128::from(total_token_0_amount),
@@ -291,6 +292,8 @@ fn main() -> Result<()> {
             let deposit_instr = deposit_instr(
                 &pool_config,
                 pool_id,
+                pool_state.amm_config,
+                pool_state.observation_key,
                 pool_state.token_0_mint,
                 pool_state.token_1_mint,
                 pool_state.lp_mint,
@@ -347,6 +350,7 @@ fn main() -> Result<()> {
                 .unwrap();
             // calculate amount
             let results = raydium_cp_swap::curve::CurveCalculator::lp_tokens_to_trading_tokens(
+                pool_state.curve(block_timestamp()).unwrap().as_ref(),
                 u128::from(lp_token_amount),
                 u128::from(pool_state.lp_supply),
                 u128::from(total_token_0_amount),
@@ -405,6 +409,8 @@ fn main() -> Result<()> {
             let withdraw_instr = withdraw_instr(
                 &pool_config,
                 pool_id,
+                pool_state.amm_config,
+                pool_state.observation_key,
                 pool_state.token_0_mint,
                 pool_state.token_1_mint,
                 pool_state.lp_mint,
@@ -451,11 +457,12 @@ fn main() -> Result<()> {
                 pool_state.token_0_mint,
                 pool_state.token_1_mint,
                 user_input_token,
+                pool_state.observation_key,
             ];
             let rsps = rpc_client.get_multiple_accounts(&load_pubkeys)?;
             let epoch = rpc_client.get_epoch_info().unwrap().epoch;
-            let [pool_account, amm_config_account, token_0_vault_account, token_1_vault_account, token_0_mint_account, token_1_mint_account, user_input_token_account] =
-                array_ref![rsps, 0, 7];
+            let [pool_account, amm_config_account, token_0_vault_account, token_1_vault_account, token_0_mint_account, token_1_mint_account, user_input_token_account, observation_account] =
+                array_ref![rsps, 0, 8];
             // docode account
             let pool_state =
                 utils::deserialize_anchor_account::<raydium_cp_swap::states::PoolState>(
@@ -471,6 +478,10 @@ fn main() -> Result<()> {
             let token_1_mint_info = unpack_mint(&token_1_mint_account.as_ref().unwrap().data)?;
             let user_input_token_info =
                 unpack_token(&user_input_token_account.as_ref().unwrap().data)?;
+            let observation_state =
+                raydium_cp_swap::states::ObservationRing::try_from_account_data(
+                    &observation_account.as_ref().unwrap().data,
+                )?;
 
             let (total_token_0_amount, total_token_1_amount) = pool_state
                 .vault_amount_without_fee(
@@ -478,6 +489,18 @@ fn main() -> Result<()> {
                     token_1_vault_info.base.amount.into(),
                 )
                 .unwrap();
+            let (token_0_price_x32, _) = pool_state
+                .token_price_x32(
+                    token_0_vault_info.base.amount.into(),
+                    token_1_vault_info.base.amount.into(),
+                    block_timestamp(),
+                )
+                .unwrap();
+            let trade_fee_rate = amm_config_state.effective_trade_fee_rate(
+                pool_state.trade_fee_rate(&amm_config_state),
+                &observation_state,
+                token_0_price_x32,
+            );
 
             let (
                 trade_direction,
@@ -532,14 +555,18 @@ fn main() -> Result<()> {
             // Take transfer fees into account for actual amount transferred in
             let actual_amount_in = user_input_amount.saturating_sub(transfer_fee);
             let result = raydium_cp_swap::curve::CurveCalculator::swap_base_input(
+                pool_state.curve(block_timestamp()).unwrap().as_ref(),
                 u128::from(actual_amount_in),
                 u128::from(total_input_token_amount),
                 u128::from(total_output_token_amount),
-                amm_config_state.trade_fee_rate,
-                pool_state.adjust_creator_fee_rate(amm_config_state.creator_fee_rate),
-                amm_config_state.protocol_fee_rate,
-                amm_config_state.fund_fee_rate,
-                pool_state.is_creator_fee_on_input(trade_direction).unwrap(),
+                trade_direction,
+                pool_state
+                    .fee_rates(
+                        &amm_config_state,
+                        trade_fee_rate,
+                        pool_state.is_creator_fee_on_input(trade_direction).unwrap(),
+                    )
+                    .unwrap(),
             )
             .ok_or(raydium_cp_swap::error::ErrorCode::ZeroTradingTokens)
             .unwrap();
@@ -609,11 +636,12 @@ fn main() -> Result<()> {
                 pool_state.token_0_mint,
                 pool_state.token_1_mint,
                 user_input_token,
+                pool_state.observation_key,
             ];
             let rsps = rpc_client.get_multiple_accounts(&load_pubkeys)?;
             let epoch = rpc_client.get_epoch_info().unwrap().epoch;
-            let [pool_account, amm_config_account, token_0_vault_account, token_1_vault_account, token_0_mint_account, token_1_mint_account, user_input_token_account] =
-                array_ref![rsps, 0, 7];
+            let [pool_account, amm_config_account, token_0_vault_account, token_1_vault_account, token_0_mint_account, token_1_mint_account, user_input_token_account, observation_account] =
+                array_ref![rsps, 0, 8];
             // docode account
             let pool_state =
                 utils::deserialize_anchor_account::<raydium_cp_swap::states::PoolState>(
@@ -629,6 +657,10 @@ fn main() -> Result<()> {
             let token_1_mint_info = unpack_mint(&token_1_mint_account.as_ref().unwrap().data)?;
             let user_input_token_info =
                 unpack_token(&user_input_token_account.as_ref().unwrap().data)?;
+            let observation_state =
+                raydium_cp_swap::states::ObservationRing::try_from_account_data(
+                    &observation_account.as_ref().unwrap().data,
+                )?;
 
             let (total_token_0_amount, total_token_1_amount) = pool_state
                 .vault_amount_without_fee(
@@ -636,6 +668,18 @@ fn main() -> Result<()> {
                     token_1_vault_info.base.amount.into(),
                 )
                 .unwrap();
+            let (token_0_price_x32, _) = pool_state
+                .token_price_x32(
+                    token_0_vault_info.base.amount.into(),
+                    token_1_vault_info.base.amount.into(),
+                    block_timestamp(),
+                )
+                .unwrap();
+            let trade_fee_rate = amm_config_state.effective_trade_fee_rate(
+                pool_state.trade_fee_rate(&amm_config_state),
+                &observation_state,
+                token_0_price_x32,
+            );
 
             let (
                 trade_direction,
@@ -689,14 +733,18 @@ fn main() -> Result<()> {
             };
             let actual_amount_out = amount_out_less_fee.checked_add(out_transfer_fee).unwrap();
             let result = raydium_cp_swap::curve::CurveCalculator::swap_base_output(
+                pool_state.curve(block_timestamp()).unwrap().as_ref(),
                 u128::from(actual_amount_out),
                 u128::from(total_input_token_amount),
                 u128::from(total_output_token_amount),
-                amm_config_state.trade_fee_rate,
-                pool_state.adjust_creator_fee_rate(amm_config_state.creator_fee_rate),
-                amm_config_state.protocol_fee_rate,
-                amm_config_state.fund_fee_rate,
-                pool_state.is_creator_fee_on_input(trade_direction).unwrap(),
+                trade_direction,
+                pool_state
+                    .fee_rates(
+                        &amm_config_state,
+                        trade_fee_rate,
+                        pool_state.is_creator_fee_on_input(trade_direction).unwrap(),
+                    )
+                    .unwrap(),
             )
             .ok_or(raydium_cp_swap::error::ErrorCode::ZeroTradingTokens)
             .unwrap();
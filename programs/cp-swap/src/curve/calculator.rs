@@ -1,9 +1,20 @@
 //! Swap calculations
 
-use crate::curve::{constant_product::ConstantProductCurve, fees::Fees};
+use crate::curve::{
+    constant_product::ConstantProductCurve,
+    fees::{FeeRates, Fees, FEE_RATE_DENOMINATOR_VALUE},
+    offset::OffsetCurve,
+    stable_swap::{StableSwapCurve, MAX_AMP, MIN_AMP},
+    weighted::{reduce_weights, WeightedCurve, MAX_WEIGHT_SUM},
+};
+use crate::utils::{U128, U256, U512};
 use anchor_lang::prelude::*;
 use {crate::error::ErrorCode, std::fmt::Debug};
 
+/// Number of swaps evaluated when searching for the swapped part of a single
+/// token deposit or a price limited swap, bounding their compute cost
+pub const MAX_SEARCH_STEPS: u8 = 10;
+
 /// Helper function for mapping to ErrorCode::CalculationFailure
 pub fn map_zero_to_none(x: u128) -> Option<u128> {
     if x == 0 {
@@ -43,6 +54,213 @@ impl TradeDirection {
             TradeDirection::OneForZero => TradeDirection::ZeroForOne,
         }
     }
+
+    /// Orders an (input, output) pair of amounts as (token_0, token_1)
+    pub fn to_token_amounts(&self, input_amount: u128, output_amount: u128) -> (u128, u128) {
+        match self {
+            TradeDirection::ZeroForOne => (input_amount, output_amount),
+            TradeDirection::OneForZero => (output_amount, input_amount),
+        }
+    }
+}
+
+/// The invariant used by a pool, stored as `PoolState.curve_type`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveType {
+    /// Uniswap-style constant product curve, x * y = k
+    ConstantProduct,
+    /// Curve-style amplified invariant for pegged pairs
+    StableSwap,
+    /// Balancer-style weighted constant mean, x^w0 * y^w1 = k
+    Weighted,
+    /// Constant product with virtual token_1 reserves, x * (y + offset) = k
+    Offset,
+}
+
+impl CurveType {
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::StableSwap),
+            2 => Ok(CurveType::Weighted),
+            3 => Ok(CurveType::Offset),
+            _ => Err(ErrorCode::InvalidCurveType.into()),
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            CurveType::ConstantProduct => 0u8,
+            CurveType::StableSwap => 1u8,
+            CurveType::Weighted => 2u8,
+            CurveType::Offset => 3u8,
+        }
+    }
+}
+
+/// The curve of a new pool along with its parameters
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveParams {
+    ConstantProduct,
+    /// `amp` is the initial amplification coefficient, it can be ramped by admin later
+    StableSwap {
+        amp: u64,
+    },
+    /// Weights are reduced by their gcd, e.g. 80/20 is stored as 4/1, and the
+    /// reduced weights must sum to at most `MAX_WEIGHT_SUM`
+    Weighted {
+        token_0_weight: u64,
+        token_1_weight: u64,
+    },
+    /// `token_1_offset` is the amount of virtual token_1 reserves, it can not be changed later
+    Offset {
+        token_1_offset: u64,
+    },
+}
+
+impl CurveParams {
+    pub fn curve_type(&self) -> CurveType {
+        match self {
+            CurveParams::ConstantProduct => CurveType::ConstantProduct,
+            CurveParams::StableSwap { .. } => CurveType::StableSwap,
+            CurveParams::Weighted { .. } => CurveType::Weighted,
+            CurveParams::Offset { .. } => CurveType::Offset,
+        }
+    }
+
+    /// Amount of virtual token_1 reserves of the pool, only offset pools have any
+    pub fn token_1_offset(&self) -> u64 {
+        match *self {
+            CurveParams::Offset { token_1_offset } => token_1_offset,
+            _ => 0,
+        }
+    }
+
+    /// Build the curve of a new pool
+    pub fn curve(&self) -> Box<dyn Curve> {
+        match *self {
+            CurveParams::ConstantProduct => Box::new(ConstantProductCurve),
+            CurveParams::StableSwap { amp } => Box::new(StableSwapCurve::new(amp)),
+            CurveParams::Weighted {
+                token_0_weight,
+                token_1_weight,
+            } => {
+                let (token_0_weight, token_1_weight) =
+                    reduce_weights(token_0_weight, token_1_weight);
+                Box::new(WeightedCurve::new(token_0_weight, token_1_weight))
+            }
+            CurveParams::Offset { token_1_offset } => Box::new(OffsetCurve::new(token_1_offset)),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            CurveParams::ConstantProduct => Ok(()),
+            CurveParams::StableSwap { amp } => {
+                if !(MIN_AMP..=MAX_AMP).contains(amp) {
+                    return err!(ErrorCode::InvalidAmp);
+                }
+                Ok(())
+            }
+            CurveParams::Weighted {
+                token_0_weight,
+                token_1_weight,
+            } => {
+                if *token_0_weight == 0 || *token_1_weight == 0 {
+                    return err!(ErrorCode::InvalidWeights);
+                }
+                let (token_0_weight, token_1_weight) =
+                    reduce_weights(*token_0_weight, *token_1_weight);
+                if token_0_weight + token_1_weight > MAX_WEIGHT_SUM {
+                    return err!(ErrorCode::WeightSumTooHigh);
+                }
+                Ok(())
+            }
+            CurveParams::Offset { token_1_offset } => {
+                if *token_1_offset == 0 {
+                    return err!(ErrorCode::InvalidOffset);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Operations a curve invariant has to provide so that it can be used by
+/// the swap, deposit and withdraw instructions. Fees are handled by
+/// `CurveCalculator` and are never seen by the curve.
+pub trait Curve: Debug {
+    /// Calculate how much output token will be provided given an amount of
+    /// input token, excluding all fees.
+    fn swap_base_input_without_fees(
+        &self,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Calculate how much input token is required to receive an amount of
+    /// output token, excluding all fees.
+    fn swap_base_output_without_fees(
+        &self,
+        output_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult>;
+
+    /// The value of the curve invariant for the given vault amounts, a swap
+    /// must never decrease it.
+    fn invariant(&self, token_0_vault_amount: u128, token_1_vault_amount: u128) -> Option<u128>;
+
+    /// Amount of lp tokens minted for the first deposit of a pool, the
+    /// geometric mean of the deposited amounts unless the curve weighs them.
+    fn initial_liquidity(&self, token_0_amount: u128, token_1_amount: u128) -> Option<u128> {
+        Some(
+            U128::from(token_0_amount)
+                .checked_mul(U128::from(token_1_amount))?
+                .integer_sqrt()
+                .as_u128(),
+        )
+    }
+
+    /// The part of the vault amounts owned by lps, which withdrawals pay out
+    fn lp_vault_amounts(
+        &self,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+    ) -> Option<(u128, u128)> {
+        Some((token_0_vault_amount, token_1_vault_amount))
+    }
+
+    /// Whether lp tokens can be minted by depositing at the pool ratio
+    fn allows_deposits(&self) -> bool {
+        true
+    }
+
+    /// The marginal price of the input token in the output token at the given
+    /// vault amounts, Q32.32. Swapping in more input token lowers it.
+    fn marginal_price_x32(
+        &self,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// The type of the curve, so that calculations can be specialized
+    fn curve_type(&self) -> CurveType;
 }
 
 /// Encodes results of depositing both sides at once
@@ -75,6 +293,32 @@ pub struct SwapResult {
     pub creator_fee: u128,
 }
 
+/// Encodes results of depositing a single token, part of which is swapped
+/// into the other token so that both are deposited at the pool ratio
+#[derive(Debug, PartialEq)]
+pub struct SingleTokenDepositResult {
+    /// The swap of part of the input token into the other token
+    pub swap: SwapResult,
+    /// Amount of input token deposited along with the swap output
+    pub deposit_input_amount: u128,
+    /// Amount of lp tokens minted for the deposit
+    pub lp_token_amount: u128,
+}
+
+/// Encodes results of withdrawing a single token, the other token withdrawn
+/// is swapped back into the pool
+#[derive(Debug, PartialEq)]
+pub struct SingleTokenWithdrawResult {
+    /// Amount of the input token withdrawn, which is swapped back into the pool
+    pub withdraw_input_amount: u128,
+    /// Amount of the output token withdrawn
+    pub withdraw_output_amount: u128,
+    /// The swap of the withdrawn input token into the output token
+    pub swap: SwapResult,
+    /// Total amount of the output token, excluding transfer fees
+    pub output_amount: u128,
+}
+
 /// Concrete struct to wrap around the trait object which performs calculation.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct CurveCalculator {}
@@ -93,15 +337,20 @@ impl CurveCalculator {
     /// Subtract fees and calculate how much destination token will be provided
     /// given an amount of source token.
     pub fn swap_base_input(
+        curve: &dyn Curve,
         input_amount: u128,
         input_vault_amount: u128,
         output_vault_amount: u128,
-        trade_fee_rate: u64,
-        creator_fee_rate: u64,
-        protocol_fee_rate: u64,
-        fund_fee_rate: u64,
-        is_creator_fee_on_input: bool,
+        trade_direction: TradeDirection,
+        fee_rates: FeeRates,
     ) -> Option<SwapResult> {
+        let FeeRates {
+            trade_fee_rate,
+            creator_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+            is_creator_fee_on_input,
+        } = fee_rates;
         let mut creator_fee = 0;
         let trade_fee: u128;
 
@@ -117,11 +366,12 @@ impl CurveCalculator {
         let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
         let fund_fee = Fees::fund_fee(trade_fee, fund_fee_rate)?;
 
-        let output_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let output_amount_swapped = curve.swap_base_input_without_fees(
             input_amount_less_fees,
             input_vault_amount,
             output_vault_amount,
-        );
+            trade_direction,
+        )?;
 
         let output_amount = if is_creator_fee_on_input {
             output_amount_swapped
@@ -143,15 +393,20 @@ impl CurveCalculator {
     }
 
     pub fn swap_base_output(
+        curve: &dyn Curve,
         output_amount: u128,
         input_vault_amount: u128,
         output_vault_amount: u128,
-        trade_fee_rate: u64,
-        creator_fee_rate: u64,
-        protocol_fee_rate: u64,
-        fund_fee_rate: u64,
-        is_creator_fee_on_input: bool,
+        trade_direction: TradeDirection,
+        fee_rates: FeeRates,
     ) -> Option<SwapResult> {
+        let FeeRates {
+            trade_fee_rate,
+            creator_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+            is_creator_fee_on_input,
+        } = fee_rates;
         let trade_fee: u128;
         let mut creator_fee = 0;
 
@@ -164,11 +419,12 @@ impl CurveCalculator {
             out_amount_with_creator_fee
         };
 
-        let input_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let input_amount_swapped = curve.swap_base_output_without_fees(
             actual_output_amount,
             input_vault_amount,
             output_vault_amount,
-        );
+            trade_direction,
+        )?;
 
         let input_amount = if is_creator_fee_on_input {
             let input_amount_with_fee = Fees::calculate_pre_fee_amount(
@@ -200,16 +456,332 @@ impl CurveCalculator {
         })
     }
 
+    /// The largest part of `input_amount` a base input swap can take before the marginal
+    /// price of the input token in the output token falls below `limit_price_x32`. At most
+    /// `MAX_SEARCH_STEPS` swaps are evaluated, so the part found may fall slightly short
+    /// of the largest one, but never passes the limit.
+    pub fn max_input_to_price_limit(
+        curve: &dyn Curve,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+        fee_rates: FeeRates,
+        limit_price_x32: u128,
+    ) -> Option<u128> {
+        let price_x32 = |swap_amount: u128| -> Option<u128> {
+            let swap = Self::swap_base_input(
+                curve,
+                swap_amount,
+                input_vault_amount,
+                output_vault_amount,
+                trade_direction,
+                fee_rates,
+            )?;
+            // The lp share of the trade fee stays in the pool
+            let input_vault_amount_after = swap
+                .new_input_vault_amount
+                .checked_add(swap.trade_fee)?
+                .checked_sub(swap.protocol_fee)?
+                .checked_sub(swap.fund_fee)?;
+            curve.marginal_price_x32(
+                input_vault_amount_after,
+                swap.new_output_vault_amount,
+                trade_direction,
+            )
+        };
+        let price_before_x32 =
+            curve.marginal_price_x32(input_vault_amount, output_vault_amount, trade_direction)?;
+        if price_before_x32 <= limit_price_x32 {
+            return Some(0);
+        }
+        let high_gap = match price_x32(input_amount) {
+            Some(price_after_x32) if price_after_x32 >= limit_price_x32 => {
+                return Some(input_amount)
+            }
+            Some(price_after_x32) => limit_price_x32 - price_after_x32,
+            // Not swappable, e.g. more than the output vault, bisect instead
+            None => price_before_x32 - limit_price_x32,
+        };
+        let (low, _) = Self::search_crossing(
+            (0, price_before_x32 - limit_price_x32),
+            (input_amount, high_gap),
+            MAX_SEARCH_STEPS - 1,
+            |swap_amount| {
+                let price_after_x32 = price_x32(swap_amount)?;
+                Some(if price_after_x32 >= limit_price_x32 {
+                    (true, price_after_x32 - limit_price_x32)
+                } else {
+                    (false, limit_price_x32 - price_after_x32)
+                })
+            },
+        );
+        Some(low)
+    }
+
+    /// Narrow down where a quantity changes sign, between `low` on one side
+    /// and `high` on the other, each given along with the size of the quantity
+    /// there. `evaluate` tells the side of an amount and the size there, or
+    /// `None` when it can not tell, which counts as the `high` side.
+    ///
+    /// Uses regula falsi, halving the size of an end that keeps its place (the
+    /// Illinois variant), and evaluates at most `steps` amounts. Returns the
+    /// narrowed `low` and `high`.
+    fn search_crossing(
+        (mut low, mut low_gap): (u128, u128),
+        (mut high, mut high_gap): (u128, u128),
+        steps: u8,
+        mut evaluate: impl FnMut(u128) -> Option<(bool, u128)>,
+    ) -> (u128, u128) {
+        let mut low_moved_last = None;
+        for _ in 0..steps {
+            if high.saturating_sub(low) <= 1 || low_gap == 0 || high_gap == 0 {
+                break;
+            }
+            let offset = U256::from(high - low)
+                .checked_mul(U256::from(low_gap))
+                .and_then(|offset| {
+                    offset.checked_div(U256::from(low_gap).checked_add(U256::from(high_gap))?)
+                })
+                .map_or(1, |offset| offset.as_u128());
+            let middle = low + offset.clamp(1, high - low - 1);
+            match evaluate(middle) {
+                Some((true, gap)) => {
+                    low = middle;
+                    low_gap = gap;
+                    if low_moved_last == Some(true) {
+                        high_gap = (high_gap / 2).max(1);
+                    }
+                    low_moved_last = Some(true);
+                }
+                Some((false, gap)) => {
+                    high = middle;
+                    high_gap = gap;
+                    if low_moved_last == Some(false) {
+                        low_gap = (low_gap / 2).max(1);
+                    }
+                    low_moved_last = Some(false);
+                }
+                // Nothing is known of the size, the next step bisects
+                None => {
+                    high = middle;
+                    high_gap = low_gap;
+                    low_moved_last = None;
+                }
+            }
+        }
+        (low, high)
+    }
+
+    /// Deposit a single token, swapping the portion of it which mints the most
+    /// lp tokens into the other token. The swap pays the normal fees and the
+    /// deposit happens at the pool ratio after the swap, rounded down.
+    ///
+    /// The portion is solved for on the constant product curve, other curves
+    /// search for it evaluating at most `MAX_SEARCH_STEPS` swaps and
+    /// may swap slightly more or less than the best portion.
+    pub fn deposit_single_token(
+        curve: &dyn Curve,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        lp_token_supply: u128,
+        trade_direction: TradeDirection,
+        fee_rates: FeeRates,
+    ) -> Option<SingleTokenDepositResult> {
+        let deposit = |swap_amount: u128| -> Option<(SwapResult, u128, u128)> {
+            let swap = Self::swap_base_input(
+                curve,
+                swap_amount,
+                input_vault_amount,
+                output_vault_amount,
+                trade_direction,
+                fee_rates,
+            )?;
+            // The lp share of the trade fee stays in the pool
+            let input_vault_amount_after = swap
+                .new_input_vault_amount
+                .checked_add(swap.trade_fee)?
+                .checked_sub(swap.protocol_fee)?
+                .checked_sub(swap.fund_fee)?;
+            let input_lp_amount = input_amount
+                .checked_sub(swap_amount)?
+                .checked_mul(lp_token_supply)?
+                .checked_div(input_vault_amount_after)?;
+            let output_lp_amount = swap
+                .output_amount
+                .checked_mul(lp_token_supply)?
+                .checked_div(swap.new_output_vault_amount)?;
+            Some((swap, input_lp_amount, output_lp_amount))
+        };
+        let mut best: Option<SingleTokenDepositResult> = None;
+        let mut evaluate = |swap_amount: u128| -> Option<(u128, u128)> {
+            let (swap, input_lp_amount, output_lp_amount) = deposit(swap_amount)?;
+            let lp_token_amount = input_lp_amount.min(output_lp_amount);
+            if best
+                .as_ref()
+                .is_none_or(|best| lp_token_amount > best.lp_token_amount)
+            {
+                best = Some(SingleTokenDepositResult {
+                    swap,
+                    deposit_input_amount: input_amount.checked_sub(swap_amount)?,
+                    lp_token_amount,
+                });
+            }
+            Some((input_lp_amount, output_lp_amount))
+        };
+        if curve.curve_type() == CurveType::ConstantProduct {
+            let swap_amount = Self::constant_product_deposit_swap_amount(
+                input_amount,
+                input_vault_amount,
+                fee_rates,
+            )?
+            .min(input_amount);
+            // Only rounding separates the solution from the best swapped amount
+            for swap_amount in [
+                swap_amount.saturating_sub(1),
+                swap_amount,
+                swap_amount.saturating_add(1).min(input_amount),
+            ] {
+                evaluate(swap_amount)?;
+            }
+        } else {
+            // The lp minted for the kept input falls and the lp minted for the
+            // swap output grows with the swapped amount, find where they cross.
+            // Nothing is swapped at 0 and the whole input is swapped at `input_amount`.
+            let low_gap = input_amount
+                .checked_mul(lp_token_supply)?
+                .checked_div(input_vault_amount)?;
+            let (input_lp_amount, output_lp_amount) = evaluate(input_amount)?;
+            Self::search_crossing(
+                (0, low_gap),
+                (
+                    input_amount,
+                    output_lp_amount.saturating_sub(input_lp_amount),
+                ),
+                MAX_SEARCH_STEPS - 1,
+                |swap_amount| {
+                    let (input_lp_amount, output_lp_amount) = evaluate(swap_amount)?;
+                    Some(if output_lp_amount <= input_lp_amount {
+                        (true, input_lp_amount - output_lp_amount)
+                    } else {
+                        (false, output_lp_amount - input_lp_amount)
+                    })
+                },
+            );
+        }
+        best
+    }
+
+    /// The amount of a constant product single token deposit to swap, where
+    /// the kept input and the swap output mint the same lp tokens.
+    ///
+    /// With x the input vault amount, a the input amount and s the swapped
+    /// amount, the user receives k * s / x of the output vault after the swap
+    /// and the input vault grows by g * s, where k and g only depend on the
+    /// fee rates. Solving (a - s) / (x + g * s) = k * s / x gives
+    /// k * g * s^2 + (1 + k) * x * s - x * a = 0. k and g are scaled by the
+    /// square of the fee rate denominator, ignoring fee rounding.
+    fn constant_product_deposit_swap_amount(
+        input_amount: u128,
+        input_vault_amount: u128,
+        fee_rates: FeeRates,
+    ) -> Option<u128> {
+        let denominator = U512::from(FEE_RATE_DENOMINATOR_VALUE);
+        let denominator_squared = denominator.checked_mul(denominator)?;
+        let (input_creator_fee_rate, output_creator_fee_rate) = if fee_rates.is_creator_fee_on_input
+        {
+            (U512::from(fee_rates.creator_fee_rate), U512::zero())
+        } else {
+            (U512::zero(), U512::from(fee_rates.creator_fee_rate))
+        };
+        let trade_fee_rate = U512::from(fee_rates.trade_fee_rate);
+        // Share of the swapped amount converted at the pool price after fees
+        let k = denominator
+            .checked_sub(trade_fee_rate)?
+            .checked_sub(input_creator_fee_rate)?
+            .checked_mul(denominator.checked_sub(output_creator_fee_rate)?)?;
+        // Share of the swapped amount staying in the input vault
+        let g = denominator_squared
+            .checked_sub(input_creator_fee_rate.checked_mul(denominator)?)?
+            .checked_sub(
+                trade_fee_rate.checked_mul(
+                    U512::from(fee_rates.protocol_fee_rate)
+                        .checked_add(U512::from(fee_rates.fund_fee_rate))?,
+                )?,
+            )?;
+        let x = U512::from(input_vault_amount);
+        let a = k.checked_mul(g)?;
+        let b = denominator_squared
+            .checked_add(k)?
+            .checked_mul(denominator_squared)?
+            .checked_mul(x)?;
+        let c = denominator_squared
+            .checked_mul(denominator_squared)?
+            .checked_mul(x)?
+            .checked_mul(U512::from(input_amount))?;
+        let discriminant = b
+            .checked_mul(b)?
+            .checked_add(U512::from(4).checked_mul(a)?.checked_mul(c)?)?;
+        let swap_amount = discriminant
+            .integer_sqrt()
+            .checked_sub(b)?
+            .checked_div(a.checked_mul(U512::from(2))?)?;
+        u128::try_from(swap_amount).ok()
+    }
+
+    /// Withdraw a single token, the pool tokens are converted to both tokens
+    /// rounded down, and the input token is swapped back into the pool for the
+    /// output token paying the normal fees.
+    pub fn withdraw_single_token(
+        curve: &dyn Curve,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+        fee_rates: FeeRates,
+    ) -> Option<SingleTokenWithdrawResult> {
+        let (token_0_vault_amount, token_1_vault_amount) =
+            trade_direction.to_token_amounts(input_vault_amount, output_vault_amount);
+        let withdraw = curve.lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            RoundDirection::Floor,
+        )?;
+        // Ordering (token_0, token_1) as (input, output) is the same swap as the reverse
+        let (withdraw_input_amount, withdraw_output_amount) =
+            trade_direction.to_token_amounts(withdraw.token_0_amount, withdraw.token_1_amount);
+        let swap = Self::swap_base_input(
+            curve,
+            withdraw_input_amount,
+            input_vault_amount.checked_sub(withdraw_input_amount)?,
+            output_vault_amount.checked_sub(withdraw_output_amount)?,
+            trade_direction,
+            fee_rates,
+        )?;
+        let output_amount = withdraw_output_amount.checked_add(swap.output_amount)?;
+        Some(SingleTokenWithdrawResult {
+            withdraw_input_amount,
+            withdraw_output_amount,
+            swap,
+            output_amount,
+        })
+    }
+
     /// Get the amount of trading tokens for the given amount of pool tokens,
     /// provided the total trading tokens and supply of pool tokens.
     pub fn lp_tokens_to_trading_tokens(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         token_0_vault_amount: u128,
         token_1_vault_amount: u128,
         round_direction: RoundDirection,
     ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
+        curve.lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             token_0_vault_amount,
@@ -217,6 +789,85 @@ impl CurveCalculator {
             round_direction,
         )
     }
+
+    /// Get the maximum amount of pool tokens the given amounts of trading
+    /// tokens can pay for, along with the trading tokens actually needed,
+    /// rounded up as in a deposit.
+    pub fn trading_tokens_to_lp_tokens(
+        curve: &dyn Curve,
+        maximum_token_0_amount: u128,
+        maximum_token_1_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+    ) -> Option<(u128, TradingTokenResult)> {
+        let lp_token_amount = maximum_token_0_amount
+            .checked_mul(lp_token_supply)?
+            .checked_div(token_0_vault_amount)?
+            .min(
+                maximum_token_1_amount
+                    .checked_mul(lp_token_supply)?
+                    .checked_div(token_1_vault_amount)?,
+            );
+        let results = curve.lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            RoundDirection::Ceiling,
+        )?;
+        if results.token_0_amount > maximum_token_0_amount
+            || results.token_1_amount > maximum_token_1_amount
+        {
+            return None;
+        }
+        Some((lp_token_amount, results))
+    }
+
+    /// Get the minimum amount of pool tokens to burn for withdrawing at least
+    /// the given amounts of trading tokens, along with the trading tokens the
+    /// burnt share pays out, rounded down as in a withdrawal. The share is
+    /// always paid out at the pool ratio, so asking for a single token also
+    /// pays out the other one.
+    pub fn lp_tokens_to_withdraw(
+        curve: &dyn Curve,
+        minimum_token_0_amount: u128,
+        minimum_token_1_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+    ) -> Option<(u128, TradingTokenResult)> {
+        let (lp_token_0_vault_amount, lp_token_1_vault_amount) =
+            curve.lp_vault_amounts(token_0_vault_amount, token_1_vault_amount)?;
+        let lp_tokens_for = |token_amount: u128, vault_amount: u128| -> Option<u128> {
+            if token_amount == 0 {
+                return Some(0);
+            }
+            if token_amount > vault_amount {
+                return None;
+            }
+            token_amount
+                .checked_mul(lp_token_supply)?
+                .checked_add(vault_amount.checked_sub(1)?)?
+                .checked_div(vault_amount)
+        };
+        let lp_token_amount = lp_tokens_for(minimum_token_0_amount, lp_token_0_vault_amount)?.max(
+            lp_tokens_for(minimum_token_1_amount, lp_token_1_vault_amount)?,
+        );
+        let results = curve.lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            RoundDirection::Floor,
+        )?;
+        if results.token_0_amount < minimum_token_0_amount
+            || results.token_1_amount < minimum_token_1_amount
+        {
+            return None;
+        }
+        Some((lp_token_amount, results))
+    }
 }
 
 /// Test helpers for curves
@@ -257,36 +908,36 @@ pub mod test {
     /// This test guarantees that the relative change in value will be at most
     /// 1 normalized token, and that the value will never decrease from a trade.
     pub fn check_curve_value_from_swap(
+        curve: &dyn Curve,
         source_token_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
         trade_direction: TradeDirection,
     ) {
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
-            source_token_amount,
-            swap_source_amount,
-            swap_destination_amount,
-        );
+        let destination_amount_swapped = curve
+            .swap_base_input_without_fees(
+                source_token_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+            )
+            .unwrap();
 
-        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
-            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
-            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
-        };
-        let previous_value = swap_token_0_amount
-            .checked_mul(swap_token_1_amount)
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(swap_source_amount, swap_destination_amount);
+        let previous_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
             .unwrap();
 
         let new_swap_source_amount = swap_source_amount.checked_add(source_token_amount).unwrap();
         let new_swap_destination_amount = swap_destination_amount
             .checked_sub(destination_amount_swapped)
             .unwrap();
-        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
-            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
-            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
-        };
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(new_swap_source_amount, new_swap_destination_amount);
 
-        let new_value = swap_token_0_amount
-            .checked_mul(swap_token_1_amount)
+        let new_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
             .unwrap();
         assert!(new_value >= previous_value);
     }
@@ -298,12 +949,14 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_deposit(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
         let deposit_result = CurveCalculator::lp_tokens_to_trading_tokens(
+            curve,
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -345,12 +998,14 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_withdraw(
+        curve: &dyn Curve,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
         let withdraw_result = CurveCalculator::lp_tokens_to_trading_tokens(
+            curve,
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
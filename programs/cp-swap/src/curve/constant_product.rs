@@ -1,8 +1,9 @@
 //! The Uniswap invariantConstantProductCurve::
 
 use crate::{
-    curve::calculator::{RoundDirection, TradingTokenResult},
-    utils::CheckedCeilDiv,
+    curve::calculator::{Curve, CurveType, RoundDirection, TradeDirection, TradingTokenResult},
+    states::Q32,
+    utils::{CheckedCeilDiv, U256},
 };
 
 /// ConstantProductCurve struct implementing CurveCalculator
@@ -88,6 +89,78 @@ impl ConstantProductCurve {
             token_1_amount,
         })
     }
+
+    /// The price y / x of the input token in the output token, Q32.32
+    pub fn marginal_price_x32(input_vault_amount: u128, output_vault_amount: u128) -> Option<u128> {
+        let price_x32 = U256::from(output_vault_amount)
+            .checked_mul(U256::from(Q32))?
+            .checked_div(U256::from(input_vault_amount))?;
+        u128::try_from(price_x32).ok()
+    }
+}
+
+impl Curve for ConstantProductCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(Self::swap_base_input_without_fees(
+            input_amount,
+            input_vault_amount,
+            output_vault_amount,
+        ))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        output_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(Self::swap_base_output_without_fees(
+            output_amount,
+            input_vault_amount,
+            output_vault_amount,
+        ))
+    }
+
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        Self::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            round_direction,
+        )
+    }
+
+    fn invariant(&self, token_0_vault_amount: u128, token_1_vault_amount: u128) -> Option<u128> {
+        token_0_vault_amount.checked_mul(token_1_vault_amount)
+    }
+
+    fn marginal_price_x32(
+        &self,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Self::marginal_price_x32(input_vault_amount, output_vault_amount)
+    }
+
+    fn curve_type(&self) -> CurveType {
+        CurveType::ConstantProduct
+    }
 }
 
 #[cfg(test)]
@@ -97,10 +170,13 @@ mod tests {
         crate::curve::calculator::{
             test::{
                 check_curve_value_from_swap, check_pool_value_from_deposit,
-                check_pool_value_from_withdraw, total_and_intermediate,
+                check_pool_value_from_withdraw, normalized_value, total_and_intermediate,
             },
-            RoundDirection, TradeDirection,
+            CurveCalculator, RoundDirection, TradeDirection,
         },
+        crate::curve::fees::FeeRates,
+        crate::states::Q32,
+        crate::utils::U256,
         proptest::prelude::*,
     };
 
@@ -224,6 +300,7 @@ mod tests {
             swap_destination_amount in 1..u64::MAX,
         ) {
             check_curve_value_from_swap(
+                &ConstantProductCurve,
                 source_token_amount as u128,
                 swap_source_amount as u128,
                 swap_destination_amount as u128,
@@ -249,6 +326,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_deposit(
+                &ConstantProductCurve,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
@@ -273,6 +351,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_withdraw(
+                &ConstantProductCurve,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
@@ -280,4 +359,281 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn deposit_single_token_swaps_about_half() {
+        // 1:1 pool without fees, a 1_000 lp share is worth 1_000 of each token
+        let result = CurveCalculator::deposit_single_token(
+            &ConstantProductCurve,
+            2_000,
+            1_000_000,
+            1_000_000,
+            1_000_000,
+            TradeDirection::ZeroForOne,
+            FeeRates::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.swap.input_amount + result.deposit_input_amount,
+            2_000
+        );
+        assert!(result.swap.input_amount > 995 && result.swap.input_amount < 1_005);
+        assert!(result.lp_token_amount >= 998 && result.lp_token_amount <= 999);
+
+        // the trade fee is charged on the swapped part only
+        let with_fee = CurveCalculator::deposit_single_token(
+            &ConstantProductCurve,
+            2_000,
+            1_000_000,
+            1_000_000,
+            1_000_000,
+            TradeDirection::ZeroForOne,
+            FeeRates {
+                trade_fee_rate: 10_000,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(with_fee.swap.trade_fee > 0);
+        assert!(with_fee.lp_token_amount < result.lp_token_amount);
+        assert!(with_fee.lp_token_amount >= 990);
+    }
+
+    #[test]
+    fn max_input_to_price_limit_stops_at_limit() {
+        let max_input = |input_amount: u128, limit_price_x32: u128| {
+            CurveCalculator::max_input_to_price_limit(
+                &ConstantProductCurve,
+                input_amount,
+                1_000_000,
+                1_000_000,
+                TradeDirection::ZeroForOne,
+                FeeRates::default(),
+                limit_price_x32,
+            )
+            .unwrap()
+        };
+        // the price falls to 0.81 once the input vault reaches 1_000_000 / 0.9
+        let limit_price_x32 = 81 * Q32 / 100;
+        let filled = max_input(1_000_000, limit_price_x32);
+        assert!(filled > 111_000 && filled <= 111_112);
+        let price_after = |input_amount: u128| {
+            let output_amount = ConstantProductCurve::swap_base_input_without_fees(
+                input_amount,
+                1_000_000,
+                1_000_000,
+            );
+            (1_000_000 - output_amount) * Q32 / (1_000_000 + input_amount)
+        };
+        assert!(price_after(filled) >= limit_price_x32);
+        assert!(price_after(filled + 1) < limit_price_x32);
+        // fully filled within the limit
+        assert_eq!(max_input(100_000, limit_price_x32), 100_000);
+        // the price is already below the limit
+        assert_eq!(max_input(100_000, Q32 + 1), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn lp_value_does_not_decrease_from_single_token_deposit(
+            input_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            lp_token_supply in 1..u64::MAX,
+            trade_fee_rate in 0..100_000u64,
+        ) {
+            let input_amount = input_amount as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            let lp_token_supply = lp_token_supply as u128;
+            if let Some(result) = CurveCalculator::deposit_single_token(
+                &ConstantProductCurve,
+                input_amount,
+                swap_source_amount,
+                swap_destination_amount,
+                lp_token_supply,
+                TradeDirection::ZeroForOne,
+                FeeRates { trade_fee_rate, ..Default::default() },
+            ) {
+                prop_assert_eq!(result.swap.input_amount + result.deposit_input_amount, input_amount);
+
+                // the whole input stays in the pool, the swap output never leaves it,
+                // so the value sqrt(token_0 * token_1) per lp token must not fall:
+                // (token_0 + input) * supply^2 >= token_0 * new_supply^2
+                let lp_token_supply = U256::from(lp_token_supply);
+                let new_lp_token_supply = lp_token_supply + U256::from(result.lp_token_amount);
+                prop_assert!(
+                    U256::from(swap_source_amount + input_amount) * lp_token_supply * lp_token_supply
+                        >= U256::from(swap_source_amount) * new_lp_token_supply * new_lp_token_supply
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn trading_tokens_to_lp_tokens_is_maximal(
+            (lp_token_supply, _) in total_and_intermediate(u64::MAX >> 1),
+            maximum_token_0_amount in 1..u64::MAX >> 1,
+            maximum_token_1_amount in 1..u64::MAX >> 1,
+            token_0_vault_amount in 1..u64::MAX >> 1,
+            token_1_vault_amount in 1..u64::MAX >> 1,
+        ) {
+            let (lp_token_amount, results) = CurveCalculator::trading_tokens_to_lp_tokens(
+                &ConstantProductCurve,
+                maximum_token_0_amount as u128,
+                maximum_token_1_amount as u128,
+                lp_token_supply as u128,
+                token_0_vault_amount as u128,
+                token_1_vault_amount as u128,
+            )
+            .unwrap();
+            prop_assert!(results.token_0_amount <= maximum_token_0_amount as u128);
+            prop_assert!(results.token_1_amount <= maximum_token_1_amount as u128);
+            // one more pool token would need more than one of the maximums
+            let lp_token_supply = lp_token_supply as u128;
+            prop_assert!(
+                (lp_token_amount + 1) * token_0_vault_amount as u128
+                    > maximum_token_0_amount as u128 * lp_token_supply
+                    || (lp_token_amount + 1) * token_1_vault_amount as u128
+                        > maximum_token_1_amount as u128 * lp_token_supply
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn lp_tokens_to_withdraw_covers_amounts(
+            (lp_token_supply, _) in total_and_intermediate(u64::MAX),
+            (token_0_vault_amount, token_0_amount) in total_and_intermediate(u64::MAX),
+            (token_1_vault_amount, token_1_amount) in total_and_intermediate(u64::MAX),
+        ) {
+            let (lp_token_amount, results) = CurveCalculator::lp_tokens_to_withdraw(
+                &ConstantProductCurve,
+                token_0_amount as u128,
+                token_1_amount as u128,
+                lp_token_supply as u128,
+                token_0_vault_amount as u128,
+                token_1_vault_amount as u128,
+            )
+            .unwrap();
+            prop_assert!(lp_token_amount <= lp_token_supply as u128);
+            prop_assert!(results.token_0_amount >= token_0_amount as u128);
+            prop_assert!(results.token_1_amount >= token_1_amount as u128);
+            // the whole burnt share is paid out, rounded down
+            prop_assert_eq!(
+                results,
+                CurveCalculator::lp_tokens_to_trading_tokens(
+                    &ConstantProductCurve,
+                    lp_token_amount,
+                    lp_token_supply as u128,
+                    token_0_vault_amount as u128,
+                    token_1_vault_amount as u128,
+                    RoundDirection::Floor,
+                )
+                .unwrap()
+            );
+            check_pool_value_from_withdraw(
+                &ConstantProductCurve,
+                lp_token_amount,
+                lp_token_supply as u128,
+                token_0_vault_amount as u128,
+                token_1_vault_amount as u128,
+            );
+        }
+    }
+
+    #[test]
+    fn lp_tokens_to_withdraw_one_sided_conserves_value() {
+        // 1:2 pool, each lp token is worth 1 token_0 and 2 token_1
+        let (lp_token_amount, results) = CurveCalculator::lp_tokens_to_withdraw(
+            &ConstantProductCurve,
+            100_000,
+            0,
+            1_000_000,
+            1_000_000,
+            2_000_000,
+        )
+        .unwrap();
+        assert_eq!(lp_token_amount, 100_000);
+        // the other side of the burnt share is paid out too, at the pool price
+        // the withdrawal is worth exactly the burnt lp
+        assert_eq!(results.token_0_amount, 100_000);
+        assert_eq!(results.token_1_amount, 200_000);
+        let withdrawn_value = results.token_0_amount * 2 + results.token_1_amount;
+        let burnt_value = lp_token_amount * (1_000_000 * 2 + 2_000_000) / 1_000_000;
+        assert_eq!(withdrawn_value, burnt_value);
+
+        // a request past the lp owned vault amounts is rejected
+        assert!(CurveCalculator::lp_tokens_to_withdraw(
+            &ConstantProductCurve,
+            0,
+            2_000_001,
+            1_000_000,
+            1_000_000,
+            2_000_000,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn withdraw_single_token_swaps_other_side_back() {
+        // a tenth of a 1:1 pool without fees
+        let result = CurveCalculator::withdraw_single_token(
+            &ConstantProductCurve,
+            100_000,
+            1_000_000,
+            1_000_000,
+            1_000_000,
+            TradeDirection::OneForZero,
+            FeeRates::default(),
+        )
+        .unwrap();
+        assert_eq!(result.withdraw_input_amount, 100_000);
+        assert_eq!(result.withdraw_output_amount, 100_000);
+        // 100_000 token_1 swapped into the remaining 900_000 / 900_000 pool
+        assert_eq!(result.swap.output_amount, 90_000);
+        assert_eq!(result.output_amount, 190_000);
+    }
+
+    proptest! {
+        #[test]
+        fn lp_value_does_not_decrease_from_single_token_withdraw(
+            (lp_token_supply, lp_token_amount) in total_and_intermediate(u64::MAX >> 16),
+            swap_source_amount in 1..u64::MAX >> 16,
+            swap_destination_amount in 1..u64::MAX >> 16,
+            trade_fee_rate in 1..100_000u64,
+        ) {
+            let lp_token_amount = lp_token_amount as u128;
+            let lp_token_supply = lp_token_supply as u128;
+            let swap_source_amount = swap_source_amount as u128;
+            let swap_destination_amount = swap_destination_amount as u128;
+            if let Some(result) = CurveCalculator::withdraw_single_token(
+                &ConstantProductCurve,
+                lp_token_amount,
+                lp_token_supply,
+                swap_source_amount,
+                swap_destination_amount,
+                TradeDirection::ZeroForOne,
+                FeeRates { trade_fee_rate, ..Default::default() },
+            ) {
+                // only the output token leaves the pool
+                let value = normalized_value(swap_source_amount, swap_destination_amount).unwrap();
+                let new_value = normalized_value(
+                    swap_source_amount,
+                    swap_destination_amount - result.output_amount,
+                )
+                .unwrap();
+                let lp_token_supply_precise =
+                    spl_math::precise_number::PreciseNumber::new(lp_token_supply).unwrap();
+                let new_lp_token_supply =
+                    spl_math::precise_number::PreciseNumber::new(lp_token_supply - lp_token_amount)
+                        .unwrap();
+                prop_assert!(new_value
+                    .checked_mul(&lp_token_supply_precise)
+                    .unwrap()
+                    .greater_than_or_equal(&value.checked_mul(&new_lp_token_supply).unwrap()));
+            }
+        }
+    }
 }
@@ -4,6 +4,17 @@ pub const FEE_RATE_DENOMINATOR_VALUE: u64 = 1_000_000;
 
 pub struct Fees {}
 
+/// The fee rates charged on a swap, denominated in hundredths of a bip (10^-6)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeRates {
+    pub trade_fee_rate: u64,
+    pub creator_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    /// Whether the creator fee is taken from the input token instead of the output token
+    pub is_creator_fee_on_input: bool,
+}
+
 fn ceil_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> Option<u128> {
     if fee_denominator == 0 {
         return None;
@@ -35,6 +46,15 @@ impl Fees {
         )
     }
 
+    /// Calculate the flash loan fee in borrowed tokens
+    pub fn flash_fee(amount: u128, flash_fee_rate: u64) -> Option<u128> {
+        ceil_div(
+            amount,
+            u128::from(flash_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
     /// Calculate the owner protocol fee in trading tokens
     pub fn protocol_fee(amount: u128, protocol_fee_rate: u64) -> Option<u128> {
         floor_div(
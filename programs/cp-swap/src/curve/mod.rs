@@ -3,7 +3,13 @@
 pub mod calculator;
 pub mod constant_product;
 pub mod fees;
+pub mod offset;
+pub mod stable_swap;
+pub mod weighted;
 
 pub use calculator::*;
 pub use constant_product::*;
 pub use fees::*;
+pub use offset::*;
+pub use stable_swap::*;
+pub use weighted::*;
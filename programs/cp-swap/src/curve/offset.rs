@@ -0,0 +1,289 @@
+//! The constant product invariant with virtual token_1 reserves, used to
+//! launch a token without seeding the other side of the pool
+//!
+//! x * (y + offset) = k
+
+use crate::curve::{
+    calculator::{Curve, CurveType, RoundDirection, TradeDirection, TradingTokenResult},
+    constant_product::ConstantProductCurve,
+};
+
+/// OffsetCurve struct implementing Curve
+///
+/// The vault amounts given to the curve include the virtual token_1 reserves,
+/// swaps price against them but can never pay out more than the real balance,
+/// and liquidity changes only ever see the real balance.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OffsetCurve {
+    /// Amount of virtual token_1 reserves
+    pub token_1_offset: u128,
+}
+
+impl OffsetCurve {
+    pub fn new(token_1_offset: u64) -> Self {
+        Self {
+            token_1_offset: u128::from(token_1_offset),
+        }
+    }
+
+    /// The real balance of the output token of a trade
+    fn real_output_vault_amount(
+        &self,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        match trade_direction {
+            TradeDirection::ZeroForOne => output_vault_amount.checked_sub(self.token_1_offset),
+            TradeDirection::OneForZero => Some(output_vault_amount),
+        }
+    }
+}
+
+impl Curve for OffsetCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let output_amount = ConstantProductCurve::swap_base_input_without_fees(
+            input_amount,
+            input_vault_amount,
+            output_vault_amount,
+        );
+        if output_amount > self.real_output_vault_amount(output_vault_amount, trade_direction)? {
+            return None;
+        }
+        Some(output_amount)
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        output_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if output_amount >= self.real_output_vault_amount(output_vault_amount, trade_direction)? {
+            return None;
+        }
+        Some(ConstantProductCurve::swap_base_output_without_fees(
+            output_amount,
+            input_vault_amount,
+            output_vault_amount,
+        ))
+    }
+
+    /// LPs only own the real token_1 balance, the virtual reserves are
+    /// removed before the ratio calculation
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        let (token_0_vault_amount, token_1_vault_amount) =
+            self.lp_vault_amounts(token_0_vault_amount, token_1_vault_amount)?;
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            round_direction,
+        )
+    }
+
+    fn lp_vault_amounts(
+        &self,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+    ) -> Option<(u128, u128)> {
+        Some((
+            token_0_vault_amount,
+            token_1_vault_amount.checked_sub(self.token_1_offset)?,
+        ))
+    }
+
+    fn invariant(&self, token_0_vault_amount: u128, token_1_vault_amount: u128) -> Option<u128> {
+        token_0_vault_amount.checked_mul(token_1_vault_amount)
+    }
+
+    /// The initial amounts are real balances, the virtual reserves count
+    /// towards the initial liquidity
+    fn initial_liquidity(&self, token_0_amount: u128, token_1_amount: u128) -> Option<u128> {
+        ConstantProductCurve.initial_liquidity(
+            token_0_amount,
+            token_1_amount.checked_add(self.token_1_offset)?,
+        )
+    }
+
+    /// Deposits at the real ratio would move the price of the pool, as the
+    /// virtual reserves do not grow with them
+    fn allows_deposits(&self) -> bool {
+        false
+    }
+
+    /// The vault amounts include the virtual reserves, which price like real ones
+    fn marginal_price_x32(
+        &self,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        ConstantProductCurve::marginal_price_x32(input_vault_amount, output_vault_amount)
+    }
+
+    fn curve_type(&self) -> CurveType {
+        CurveType::Offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{check_curve_value_from_swap, check_pool_value_from_withdraw},
+            CurveCalculator, RoundDirection, TradeDirection,
+        },
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn swap_against_virtual_reserves() {
+        // 1_000_000 token_0 seeded against 1_000_000 virtual token_1
+        let curve = OffsetCurve::new(1_000_000);
+        // buying token_0 prices against the virtual reserves
+        assert_eq!(
+            curve
+                .swap_base_input_without_fees(
+                    1_000_000,
+                    1_000_000,
+                    1_000_000,
+                    TradeDirection::OneForZero
+                )
+                .unwrap(),
+            500_000
+        );
+        // no real token_1 can be paid out yet
+        assert!(curve
+            .swap_base_input_without_fees(1_000, 1_000_000, 1_000_000, TradeDirection::ZeroForOne)
+            .is_none());
+        assert!(curve
+            .swap_base_output_without_fees(1, 1_000_000, 1_000_000, TradeDirection::ZeroForOne)
+            .is_none());
+        // once token_0 was bought, it can be sold back for the real token_1
+        assert_eq!(
+            curve
+                .swap_base_input_without_fees(
+                    500_000,
+                    500_000,
+                    2_000_000,
+                    TradeDirection::ZeroForOne
+                )
+                .unwrap(),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn withdraw_excludes_virtual_reserves() {
+        let curve = OffsetCurve::new(1_000_000);
+        let results = curve
+            .lp_tokens_to_trading_tokens(50, 100, 1_000_000, 1_000_000, RoundDirection::Floor)
+            .unwrap();
+        assert_eq!(results.token_0_amount, 500_000);
+        assert_eq!(results.token_1_amount, 0);
+        let results = curve
+            .lp_tokens_to_trading_tokens(50, 100, 1_000_000, 1_400_000, RoundDirection::Floor)
+            .unwrap();
+        assert_eq!(results.token_1_amount, 200_000);
+        assert!(curve
+            .lp_tokens_to_trading_tokens(50, 100, 1_000_000, 999_999, RoundDirection::Floor)
+            .is_none());
+        // withdrawing by amounts prices the lp against the real token_1 only
+        let (lp_token_amount, results) =
+            CurveCalculator::lp_tokens_to_withdraw(&curve, 0, 200_000, 100, 1_000_000, 1_400_000)
+                .unwrap();
+        assert_eq!(lp_token_amount, 50);
+        assert_eq!(results.token_0_amount, 500_000);
+        assert_eq!(results.token_1_amount, 200_000);
+    }
+
+    #[test]
+    fn offset_initial_liquidity() {
+        let curve = OffsetCurve::new(1_000_000);
+        assert_eq!(curve.initial_liquidity(1_000_000, 0).unwrap(), 1_000_000);
+        assert!(!curve.allows_deposits());
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX,
+            token_0_amount in 1..u64::MAX,
+            real_token_1_amount in 0..u64::MAX / 2,
+            token_1_offset in 1..u64::MAX / 2,
+        ) {
+            let curve = OffsetCurve::new(token_1_offset);
+            let token_1_amount = u128::from(real_token_1_amount) + u128::from(token_1_offset);
+            for (trade_direction, swap_source_amount, swap_destination_amount) in [
+                (TradeDirection::ZeroForOne, u128::from(token_0_amount), token_1_amount),
+                (TradeDirection::OneForZero, token_1_amount, u128::from(token_0_amount)),
+            ] {
+                if let Some(output_amount) = curve.swap_base_input_without_fees(
+                    u128::from(source_token_amount),
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                ) {
+                    let real_output_vault_amount = match trade_direction {
+                        TradeDirection::ZeroForOne => u128::from(real_token_1_amount),
+                        TradeDirection::OneForZero => u128::from(token_0_amount),
+                    };
+                    prop_assert!(output_amount <= real_output_vault_amount);
+                    check_curve_value_from_swap(
+                        &curve,
+                        u128::from(source_token_amount),
+                        swap_source_amount,
+                        swap_destination_amount,
+                        trade_direction,
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn pool_value_does_not_decrease_from_withdraw(
+            (lp_token_supply, lp_token_amount) in (2..u64::MAX).prop_flat_map(|supply| (Just(supply), 1..supply)),
+            token_0_amount in 1..u64::MAX,
+            real_token_1_amount in 0..u64::MAX / 2,
+            token_1_offset in 1..u64::MAX / 2,
+        ) {
+            let curve = OffsetCurve::new(token_1_offset);
+            let token_1_amount = u128::from(real_token_1_amount) + u128::from(token_1_offset);
+            let results = curve
+                .lp_tokens_to_trading_tokens(
+                    u128::from(lp_token_amount),
+                    u128::from(lp_token_supply),
+                    u128::from(token_0_amount),
+                    token_1_amount,
+                    RoundDirection::Floor,
+                )
+                .unwrap();
+            prop_assert!(results.token_1_amount <= u128::from(real_token_1_amount));
+            check_pool_value_from_withdraw(
+                &curve,
+                u128::from(lp_token_amount),
+                u128::from(lp_token_supply),
+                u128::from(token_0_amount),
+                token_1_amount,
+            );
+        }
+    }
+}
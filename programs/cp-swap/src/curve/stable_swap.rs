@@ -0,0 +1,530 @@
+//! The Curve StableSwap invariant for two tokens
+//!
+//! A * n^n * (x + y) + D = A * D * n^n + D^(n + 1) / (n^n * x * y)
+
+use crate::{
+    curve::{
+        calculator::{Curve, CurveType, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    states::Q32,
+    utils::{U256, U512},
+};
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+/// Maximum factor the amplification coefficient can change by in one ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// Minimum duration of an amplification coefficient ramp, in seconds
+pub const MIN_RAMP_DURATION: u64 = 86400;
+
+/// Number of tokens in the pool
+const N_COINS: u64 = 2;
+/// Newton's method converges in a handful of rounds, this only bounds the loop
+const MAX_ITERATIONS: u16 = 256;
+/// Newton's method lands within a unit of the root, so settling its result on the exact
+/// rounding takes a step or two. A result further off means it did not converge.
+const MAX_ADJUSTMENTS: u8 = 4;
+
+/// StableSwapCurve struct implementing Curve
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableSwapCurve {
+    /// Amplification coefficient
+    pub amp: u64,
+}
+
+impl StableSwapCurve {
+    pub fn new(amp: u64) -> Self {
+        Self { amp }
+    }
+
+    /// A * n^n
+    fn ann(&self) -> U256 {
+        U256::from(self.amp) * U256::from(N_COINS * N_COINS)
+    }
+
+    /// Whether the invariant of the balances `x` and `y` is at least `d`.
+    ///
+    /// The left hand side of the invariant minus its right hand side decreases
+    /// with D, so D(x, y) >= d exactly when
+    /// 4xy * (Ann * (x + y) + d) >= 4xy * Ann * d + d^3
+    fn is_above(&self, x: u128, y: u128, d: U256) -> Option<bool> {
+        let ann = self.ann();
+        let xy4 = U256::from(x)
+            .checked_mul(U256::from(y))?
+            .checked_mul(U256::from(N_COINS * N_COINS))?;
+        let sum = U256::from(x).checked_add(U256::from(y))?;
+        let lhs = xy4.checked_mul(ann.checked_mul(sum)?.checked_add(d)?)?;
+        let rhs = xy4
+            .checked_mul(ann)?
+            .checked_mul(d)?
+            .checked_add(d.checked_mul(d)?.checked_mul(d)?)?;
+        Some(lhs >= rhs)
+    }
+
+    /// Compute the invariant D of the given balances, rounded down.
+    pub fn compute_d(&self, x: u128, y: u128) -> Option<U256> {
+        if x == 0 || y == 0 {
+            return Some(U256::zero());
+        }
+        let ann = self.ann();
+        let sum = U256::from(x).checked_add(U256::from(y))?;
+        let n_coins = U256::from(N_COINS);
+        let mut d = sum;
+        for _ in 0..MAX_ITERATIONS {
+            // d_p = D^3 / (4 * x * y), with a single rounding so Newton's method settles
+            // within a unit of the root even for very imbalanced pools
+            let d_cubed = to_u512(d)
+                .checked_mul(to_u512(d))?
+                .checked_mul(to_u512(d))?;
+            let d_p = to_u256(
+                d_cubed.checked_div(
+                    U512::from(x)
+                        .checked_mul(U512::from(y))?
+                        .checked_mul(U512::from(N_COINS * N_COINS))?,
+                )?,
+            )?;
+            let d_prev = d;
+            // D = (Ann * S + 2 * d_p) * D / ((Ann - 1) * D + 3 * d_p)
+            let numerator = ann
+                .checked_mul(sum)?
+                .checked_add(d_p.checked_mul(n_coins)?)?
+                .checked_mul(d)?;
+            let denominator = ann
+                .checked_sub(U256::one())?
+                .checked_mul(d)?
+                .checked_add(d_p.checked_mul(n_coins.checked_add(U256::one())?)?)?;
+            d = numerator.checked_div(denominator)?;
+            let diff = if d > d_prev { d - d_prev } else { d_prev - d };
+            if diff <= U256::one() {
+                break;
+            }
+        }
+        // Newton's method lands within a unit of the root, settle it on the floor
+        let mut adjustments = 0;
+        while !d.is_zero() && !self.is_above(x, y, d)? {
+            adjustments += 1;
+            if adjustments > MAX_ADJUSTMENTS {
+                return None;
+            }
+            d = d.checked_sub(U256::one())?;
+        }
+        while self.is_above(x, y, d.checked_add(U256::one())?)? {
+            adjustments += 1;
+            if adjustments > MAX_ADJUSTMENTS {
+                return None;
+            }
+            d = d.checked_add(U256::one())?;
+        }
+        Some(d)
+    }
+
+    /// Compute the smallest balance of the other token keeping the invariant
+    /// of the pool at least `d`, given the balance `x` of one token.
+    pub fn compute_y(&self, x: u128, d: U256) -> Option<u128> {
+        if x == 0 {
+            return None;
+        }
+        let ann = self.ann();
+        let n_coins = U256::from(N_COINS);
+        // c = D^3 / (4 * x * Ann)
+        let c = d
+            .checked_mul(d)?
+            .checked_div(U256::from(x).checked_mul(n_coins)?)?
+            .checked_mul(d)?
+            .checked_div(ann.checked_mul(n_coins)?)?;
+        // b = x + D / Ann
+        let b = U256::from(x).checked_add(d.checked_div(ann)?)?;
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let y_prev = y;
+            // y = (y^2 + c) / (2 * y + b - D)
+            y = y
+                .checked_mul(y)?
+                .checked_add(c)?
+                .checked_div(y.checked_mul(n_coins)?.checked_add(b)?.checked_sub(d)?)?;
+            let diff = if y > y_prev { y - y_prev } else { y_prev - y };
+            if diff <= U256::one() {
+                break;
+            }
+        }
+        let mut y = to_u128(y)?;
+        // Round against the trader so the swap never lowers the invariant
+        let mut adjustments = 0;
+        while !self.is_above(x, y, d)? {
+            adjustments += 1;
+            if adjustments > MAX_ADJUSTMENTS {
+                return None;
+            }
+            y = y.checked_add(1)?;
+        }
+        while y > 1 && self.is_above(x, y - 1, d)? {
+            adjustments += 1;
+            if adjustments > MAX_ADJUSTMENTS {
+                return None;
+            }
+            y -= 1;
+        }
+        Some(y)
+    }
+}
+
+fn to_u128(value: U256) -> Option<u128> {
+    if value > U256::from(u128::MAX) {
+        None
+    } else {
+        Some(value.as_u128())
+    }
+}
+
+fn to_u512(value: U256) -> U512 {
+    let mut words = [0u64; 8];
+    words[..4].copy_from_slice(&value.0);
+    U512(words)
+}
+
+fn to_u256(value: U512) -> Option<U256> {
+    if value.0[4..].iter().any(|word| *word != 0) {
+        return None;
+    }
+    let mut words = [0u64; 4];
+    words.copy_from_slice(&value.0[..4]);
+    Some(U256(words))
+}
+
+impl Curve for StableSwapCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = self.compute_d(input_vault_amount, output_vault_amount)?;
+        let new_input_vault_amount = input_vault_amount.checked_add(input_amount)?;
+        let new_output_vault_amount = self.compute_y(new_input_vault_amount, d)?;
+        output_vault_amount.checked_sub(new_output_vault_amount)
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        output_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if output_amount >= output_vault_amount {
+            return None;
+        }
+        let d = self.compute_d(input_vault_amount, output_vault_amount)?;
+        let new_output_vault_amount = output_vault_amount.checked_sub(output_amount)?;
+        let new_input_vault_amount = self.compute_y(new_output_vault_amount, d)?;
+        new_input_vault_amount.checked_sub(input_vault_amount)
+    }
+
+    /// Deposits and withdrawals keep the pool ratio, so the conversion is the
+    /// same simple ratio calculation as the constant product curve
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            round_direction,
+        )
+    }
+
+    fn invariant(&self, token_0_vault_amount: u128, token_1_vault_amount: u128) -> Option<u128> {
+        to_u128(self.compute_d(token_0_vault_amount, token_1_vault_amount)?)
+    }
+
+    /// The ratio of the partial derivatives of the invariant, multiplied by 4x^2y^2:
+    /// (4 * Ann * x^2 * y^2 + D^3 * y) / (4 * Ann * x^2 * y^2 + D^3 * x)
+    fn marginal_price_x32(
+        &self,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let d = U512::from(to_u128(
+            self.compute_d(input_vault_amount, output_vault_amount)?,
+        )?);
+        let d_cubed = d.checked_mul(d)?.checked_mul(d)?;
+        let xy = U512::from(input_vault_amount).checked_mul(U512::from(output_vault_amount))?;
+        let ann_term = U512::from(self.amp)
+            .checked_mul(U512::from(4 * N_COINS * N_COINS))?
+            .checked_mul(xy)?
+            .checked_mul(xy)?;
+        let numerator =
+            ann_term.checked_add(d_cubed.checked_mul(U512::from(output_vault_amount))?)?;
+        let denominator =
+            ann_term.checked_add(d_cubed.checked_mul(U512::from(input_vault_amount))?)?;
+        let price_x32 = numerator
+            .checked_mul(U512::from(Q32))?
+            .checked_div(denominator)?;
+        u128::try_from(price_x32).ok()
+    }
+
+    fn curve_type(&self) -> CurveType {
+        CurveType::StableSwap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{
+                check_curve_value_from_swap, check_pool_value_from_deposit,
+                check_pool_value_from_withdraw, total_and_intermediate,
+            },
+            CurveCalculator, RoundDirection, TradeDirection,
+        },
+        crate::curve::fees::FeeRates,
+        crate::states::Q32,
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn compute_d_balanced_pool() {
+        let curve = StableSwapCurve::new(100);
+        // a balanced pool has D = x + y whatever the amplification
+        assert_eq!(curve.invariant(1_000_000, 1_000_000).unwrap(), 2_000_000);
+        assert_eq!(curve.invariant(0, 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn compute_d_settles_on_imbalanced_pool() {
+        // Newton's method used to stop tens of units away from the root here
+        let curve = StableSwapCurve::new(722_734);
+        let (x, y) = (14_589_139_020_057_498_238, 1_570_073);
+        let d = curve.compute_d(x, y).unwrap();
+        assert!(curve.is_above(x, y, d).unwrap());
+        assert!(!curve.is_above(x, y, d + 1).unwrap());
+        let new_y = curve.compute_y(x, d).unwrap();
+        assert!(curve.is_above(x, new_y, d).unwrap());
+        assert!(!curve.is_above(x, new_y - 1, d).unwrap());
+    }
+
+    #[test]
+    fn stable_swap_beats_constant_product_near_peg() {
+        let curve = StableSwapCurve::new(100);
+        let stable_out = curve
+            .swap_base_input_without_fees(10_000, 1_000_000, 1_000_000, TradeDirection::ZeroForOne)
+            .unwrap();
+        let constant_product_out =
+            ConstantProductCurve::swap_base_input_without_fees(10_000, 1_000_000, 1_000_000);
+        assert!(stable_out > constant_product_out);
+        assert!(stable_out < 10_000);
+
+        let input = curve
+            .swap_base_output_without_fees(
+                stable_out,
+                1_000_000,
+                1_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .unwrap();
+        assert!(input <= 10_000);
+    }
+
+    #[test]
+    fn deposit_single_token_search_is_close() {
+        let curve = StableSwapCurve::new(100);
+        let fee_rates = FeeRates {
+            trade_fee_rate: 2_500,
+            ..Default::default()
+        };
+        for (input_amount, token_0_vault_amount, token_1_vault_amount) in [
+            (10_000_000, 1_000_000_000, 1_000_000_000),
+            (10_000_000, 1_500_000_000, 500_000_000),
+            (800_000_000, 1_000_000_000, 1_000_000_000),
+        ] {
+            let result = CurveCalculator::deposit_single_token(
+                &curve,
+                input_amount,
+                token_0_vault_amount,
+                token_1_vault_amount,
+                2_000_000_000,
+                TradeDirection::ZeroForOne,
+                fee_rates,
+            )
+            .unwrap();
+            // the search evaluates at most MAX_SEARCH_STEPS swaps, an
+            // exhaustive bisection must not mint noticeably more lp tokens
+            let (mut low, mut high) = (0u128, input_amount);
+            let mut best = 0;
+            while low < high {
+                let middle = low + (high - low).div_ceil(2);
+                let swap = CurveCalculator::swap_base_input(
+                    &curve,
+                    middle,
+                    token_0_vault_amount,
+                    token_1_vault_amount,
+                    TradeDirection::ZeroForOne,
+                    fee_rates,
+                )
+                .unwrap();
+                let input_lp_amount = (input_amount - middle) * 2_000_000_000
+                    / (swap.new_input_vault_amount + swap.trade_fee);
+                let output_lp_amount =
+                    swap.output_amount * 2_000_000_000 / swap.new_output_vault_amount;
+                best = best.max(input_lp_amount.min(output_lp_amount));
+                if output_lp_amount <= input_lp_amount {
+                    low = middle;
+                } else {
+                    high = middle - 1;
+                }
+            }
+            assert!(result.lp_token_amount <= best);
+            assert!(result.lp_token_amount * 10_000 >= best * 9_999);
+        }
+    }
+
+    #[test]
+    fn max_input_to_price_limit_uses_marginal_price() {
+        let curve = StableSwapCurve::new(100);
+        let price_x32 = |input_vault_amount: u128, output_vault_amount: u128| {
+            curve
+                .marginal_price_x32(
+                    input_vault_amount,
+                    output_vault_amount,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap()
+        };
+        // a balanced pool trades at par, an imbalanced one close to it unlike its
+        // reserve ratio of 1/2
+        assert_eq!(price_x32(1_000_000, 1_000_000), Q32);
+        let price_imbalanced_x32 = price_x32(2_000_000, 1_000_000);
+        assert!(price_imbalanced_x32 < Q32 && price_imbalanced_x32 > Q32 * 9 / 10);
+
+        let limit_price_x32 = Q32 * 99 / 100;
+        let filled = CurveCalculator::max_input_to_price_limit(
+            &curve,
+            1_000_000_000,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::ZeroForOne,
+            FeeRates::default(),
+            limit_price_x32,
+        )
+        .unwrap();
+        let price_after_x32 = |input_amount: u128| {
+            let output_amount = curve
+                .swap_base_input_without_fees(
+                    input_amount,
+                    1_000_000_000,
+                    1_000_000_000,
+                    TradeDirection::ZeroForOne,
+                )
+                .unwrap();
+            price_x32(1_000_000_000 + input_amount, 1_000_000_000 - output_amount)
+        };
+        // never past the limit, and within a bip of the largest amount
+        assert!(price_after_x32(filled) >= limit_price_x32);
+        assert!(price_after_x32(filled + filled / 10_000) < limit_price_x32);
+    }
+
+    #[test]
+    fn fail_swap_base_output_whole_vault() {
+        let curve = StableSwapCurve::new(100);
+        let result = curve.swap_base_output_without_fees(
+            1_000_000,
+            1_000_000,
+            1_000_000,
+            TradeDirection::ZeroForOne,
+        );
+        assert!(result.is_none());
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            check_curve_value_from_swap(
+                &StableSwapCurve::new(amp),
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                &StableSwapCurve::new(amp),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..MAX_AMP,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &StableSwapCurve::new(amp),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    #[test]
+    fn stable_swap_lp_conversion_matches_ratio() {
+        let results = StableSwapCurve::new(100)
+            .lp_tokens_to_trading_tokens(5, 10, 2, 49, RoundDirection::Ceiling)
+            .unwrap();
+        assert_eq!(results.token_0_amount, 1);
+        assert_eq!(results.token_1_amount, 25);
+    }
+}
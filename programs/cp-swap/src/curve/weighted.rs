@@ -0,0 +1,450 @@
+//! The Balancer weighted constant mean invariant for two tokens
+//!
+//! x^w0 * y^w1 = k
+
+use crate::{
+    curve::{
+        calculator::{Curve, CurveType, RoundDirection, TradeDirection, TradingTokenResult},
+        constant_product::ConstantProductCurve,
+    },
+    states::Q32,
+    utils::U512,
+};
+
+/// Upper bound of the sum of the reduced token weights, so that the invariant
+/// of u64 balances always fits in a U512. This allows 50/50, 60/40, 75/25,
+/// 80/20 and the other ratios of small integers, but not 70/30 (7/3) or 90/10 (9/1).
+pub const MAX_WEIGHT_SUM: u64 = 7;
+
+/// Reduce a pair of weights by their greatest common divisor, so that an
+/// 80/20 pool is stored as 4/1
+pub fn reduce_weights(token_0_weight: u64, token_1_weight: u64) -> (u64, u64) {
+    let (mut a, mut b) = (token_0_weight, token_1_weight);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    if a == 0 {
+        return (token_0_weight, token_1_weight);
+    }
+    (token_0_weight / a, token_1_weight / a)
+}
+
+fn checked_pow(base: U512, exp: u64) -> Option<U512> {
+    let mut result = U512::one();
+    for _ in 0..exp {
+        result = result.checked_mul(base)?;
+    }
+    Some(result)
+}
+
+/// Floor of the n-th root of value
+fn integer_root(value: U512, n: u64) -> Option<U512> {
+    if value.is_zero() || n == 1 {
+        return Some(value);
+    }
+    let n_u512 = U512::from(n);
+    // Start from a power of two above the root and walk down with Newton's method
+    let bits = u64::try_from(value.bits()).ok()?;
+    let mut root = U512::one() << bits.checked_add(n - 1)?.checked_div(n)?;
+    loop {
+        let next = root
+            .checked_mul(n_u512.checked_sub(U512::one())?)?
+            .checked_add(value.checked_div(checked_pow(root, n - 1)?)?)?
+            .checked_div(n_u512)?;
+        if next >= root {
+            return Some(root);
+        }
+        root = next;
+    }
+}
+
+/// Ceiling of the n-th root of value
+fn integer_root_ceil(value: U512, n: u64) -> Option<U512> {
+    let root = integer_root(value, n)?;
+    if checked_pow(root, n)? < value {
+        root.checked_add(U512::one())
+    } else {
+        Some(root)
+    }
+}
+
+fn to_u128(value: U512) -> Option<u128> {
+    if value > U512::from(u128::MAX) {
+        None
+    } else {
+        Some(value.as_u128())
+    }
+}
+
+/// WeightedCurve struct implementing Curve
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Reduced weight of token_0
+    pub token_0_weight: u64,
+    /// Reduced weight of token_1
+    pub token_1_weight: u64,
+}
+
+impl WeightedCurve {
+    pub fn new(token_0_weight: u64, token_1_weight: u64) -> Self {
+        Self {
+            token_0_weight,
+            token_1_weight,
+        }
+    }
+
+    /// Weights of the (input, output) tokens
+    fn weights(&self, trade_direction: TradeDirection) -> (u64, u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => (self.token_0_weight, self.token_1_weight),
+            TradeDirection::OneForZero => (self.token_1_weight, self.token_0_weight),
+        }
+    }
+
+    /// x^wx * y^wy
+    fn product(x: u128, x_weight: u64, y: u128, y_weight: u64) -> Option<U512> {
+        checked_pow(U512::from(x), x_weight)?.checked_mul(checked_pow(U512::from(y), y_weight)?)
+    }
+
+    /// The smallest balance of the other token keeping the product at least `k`,
+    /// given the balance `x` of one token.
+    fn compute_y(k: U512, x: u128, x_weight: u64, y_weight: u64) -> Option<u128> {
+        let x_pow = checked_pow(U512::from(x), x_weight)?;
+        if x_pow.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = k.div_mod(x_pow);
+        let y_pow = if remainder.is_zero() {
+            quotient
+        } else {
+            quotient.checked_add(U512::one())?
+        };
+        to_u128(integer_root_ceil(y_pow, y_weight)?)
+    }
+}
+
+impl Curve for WeightedCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        input_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (input_weight, output_weight) = self.weights(trade_direction);
+        let k = Self::product(
+            input_vault_amount,
+            input_weight,
+            output_vault_amount,
+            output_weight,
+        )?;
+        let new_input_vault_amount = input_vault_amount.checked_add(input_amount)?;
+        let new_output_vault_amount =
+            Self::compute_y(k, new_input_vault_amount, input_weight, output_weight)?;
+        output_vault_amount.checked_sub(new_output_vault_amount)
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        output_amount: u128,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        if output_amount >= output_vault_amount {
+            return None;
+        }
+        let (input_weight, output_weight) = self.weights(trade_direction);
+        let k = Self::product(
+            input_vault_amount,
+            input_weight,
+            output_vault_amount,
+            output_weight,
+        )?;
+        let new_output_vault_amount = output_vault_amount.checked_sub(output_amount)?;
+        let new_input_vault_amount =
+            Self::compute_y(k, new_output_vault_amount, output_weight, input_weight)?;
+        new_input_vault_amount.checked_sub(input_vault_amount)
+    }
+
+    /// Deposits and withdrawals keep the pool ratio, so the conversion is the
+    /// same simple ratio calculation as the constant product curve
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        token_0_vault_amount: u128,
+        token_1_vault_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            token_0_vault_amount,
+            token_1_vault_amount,
+            round_direction,
+        )
+    }
+
+    /// The weighted geometric mean of the balances, rounded down
+    fn invariant(&self, token_0_vault_amount: u128, token_1_vault_amount: u128) -> Option<u128> {
+        let product = Self::product(
+            token_0_vault_amount,
+            self.token_0_weight,
+            token_1_vault_amount,
+            self.token_1_weight,
+        )?;
+        to_u128(integer_root(
+            product,
+            self.token_0_weight.checked_add(self.token_1_weight)?,
+        )?)
+    }
+
+    fn initial_liquidity(&self, token_0_amount: u128, token_1_amount: u128) -> Option<u128> {
+        self.invariant(token_0_amount, token_1_amount)
+    }
+
+    /// The price (y * wx) / (x * wy) of the input token in the output token
+    fn marginal_price_x32(
+        &self,
+        input_vault_amount: u128,
+        output_vault_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (input_weight, output_weight) = self.weights(trade_direction);
+        let price_x32 = U512::from(output_vault_amount)
+            .checked_mul(U512::from(input_weight))?
+            .checked_mul(U512::from(Q32))?
+            .checked_div(U512::from(input_vault_amount).checked_mul(U512::from(output_weight))?)?;
+        to_u128(price_x32)
+    }
+
+    fn curve_type(&self) -> CurveType {
+        CurveType::Weighted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{
+                check_curve_value_from_swap, check_pool_value_from_deposit,
+                check_pool_value_from_withdraw, total_and_intermediate,
+            },
+            CurveParams, TradeDirection,
+        },
+        crate::error::ErrorCode,
+        proptest::prelude::*,
+    };
+
+    prop_compose! {
+        fn weights()(token_0_weight in 1..MAX_WEIGHT_SUM)
+                    (token_1_weight in 1..=MAX_WEIGHT_SUM - token_0_weight,
+                     token_0_weight in Just(token_0_weight))
+                    -> (u64, u64) {
+            (token_0_weight, token_1_weight)
+        }
+    }
+
+    #[test]
+    fn reduce_weights_test() {
+        assert_eq!(reduce_weights(80, 20), (4, 1));
+        assert_eq!(reduce_weights(60, 40), (3, 2));
+        assert_eq!(reduce_weights(50, 50), (1, 1));
+        assert_eq!(reduce_weights(75, 25), (3, 1));
+    }
+
+    #[test]
+    fn weighted_params_validate_test() {
+        let params = |token_0_weight, token_1_weight| CurveParams::Weighted {
+            token_0_weight,
+            token_1_weight,
+        };
+        assert!(params(80, 20).validate().is_ok());
+        assert!(params(60, 40).validate().is_ok());
+        assert!(params(1, 6).validate().is_ok());
+        assert_eq!(
+            params(0, 20).validate().unwrap_err(),
+            ErrorCode::InvalidWeights.into()
+        );
+        // 7/3 and 9/1 do not fit in the U512 invariant
+        assert_eq!(
+            params(70, 30).validate().unwrap_err(),
+            ErrorCode::WeightSumTooHigh.into()
+        );
+        assert_eq!(
+            params(90, 10).validate().unwrap_err(),
+            ErrorCode::WeightSumTooHigh.into()
+        );
+    }
+
+    #[test]
+    fn integer_root_test() {
+        assert_eq!(integer_root(U512::from(16u8), 2).unwrap(), U512::from(4u8));
+        assert_eq!(integer_root(U512::from(17u8), 2).unwrap(), U512::from(4u8));
+        assert_eq!(
+            integer_root_ceil(U512::from(17u8), 2).unwrap(),
+            U512::from(5u8)
+        );
+        assert_eq!(integer_root(U512::from(80u8), 4).unwrap(), U512::from(2u8));
+        assert_eq!(integer_root(U512::from(81u8), 4).unwrap(), U512::from(3u8));
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let curve = WeightedCurve::new(1, 1);
+        let tests: &[(u128, u128, u128)] = &[
+            (10, 4_000_000, 70_000_000_000),
+            (20, 30_000 - 20, 10_000),
+            (100, 60_000, 30_000),
+            (98, 60_000, 30_000),
+        ];
+        for (source_amount, swap_source_amount, swap_destination_amount) in tests.iter() {
+            assert_eq!(
+                curve
+                    .swap_base_input_without_fees(
+                        *source_amount,
+                        *swap_source_amount,
+                        *swap_destination_amount,
+                        TradeDirection::ZeroForOne,
+                    )
+                    .unwrap(),
+                ConstantProductCurve::swap_base_input_without_fees(
+                    *source_amount,
+                    *swap_source_amount,
+                    *swap_destination_amount,
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn weighted_swap_matches_closed_form() {
+        // 80/20 pool holding 4 times more value in token_0, priced 1:1
+        let curve = WeightedCurve::new(4, 1);
+        // 1_000_000 * (1 - (4 / 5)^4)
+        assert_eq!(
+            curve
+                .swap_base_input_without_fees(
+                    1_000_000,
+                    4_000_000,
+                    1_000_000,
+                    TradeDirection::ZeroForOne
+                )
+                .unwrap(),
+            590_400
+        );
+        // 4_000_000 * (1 - (1 / 2)^(1 / 4)), rounded in favor of the pool
+        assert_eq!(
+            curve
+                .swap_base_input_without_fees(
+                    1_000_000,
+                    1_000_000,
+                    4_000_000,
+                    TradeDirection::OneForZero
+                )
+                .unwrap(),
+            636_414
+        );
+        assert_eq!(
+            curve
+                .swap_base_output_without_fees(
+                    590_400,
+                    4_000_000,
+                    1_000_000,
+                    TradeDirection::ZeroForOne
+                )
+                .unwrap(),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn weighted_initial_liquidity() {
+        let curve = WeightedCurve::new(4, 1);
+        // (16^4 * 1)^(1/5) = 9.18
+        assert_eq!(curve.initial_liquidity(16, 1).unwrap(), 9);
+        assert_eq!(curve.initial_liquidity(1_000, 1_000).unwrap(), 1_000);
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap(
+            source_token_amount in 1..u64::MAX,
+            swap_source_amount in 1..u64::MAX,
+            swap_destination_amount in 1..u64::MAX,
+            (token_0_weight, token_1_weight) in weights(),
+        ) {
+            let curve = WeightedCurve::new(token_0_weight, token_1_weight);
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne
+            );
+            check_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::OneForZero
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            (token_0_weight, token_1_weight) in weights(),
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                &WeightedCurve::new(token_0_weight, token_1_weight),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            (token_0_weight, token_1_weight) in weights(),
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                &WeightedCurve::new(token_0_weight, token_1_weight),
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
@@ -40,4 +40,48 @@ pub enum ErrorCode {
     InvalidFeeModel,
     #[msg("Fee is zero")]
     NoFeeCollect,
+    #[msg("Invalid curve type")]
+    InvalidCurveType,
+    #[msg("Amplification coefficient out of range")]
+    InvalidAmp,
+    #[msg("Invalid amplification coefficient ramp")]
+    InvalidAmpRamp,
+    #[msg("Invalid token weights")]
+    InvalidWeights,
+    #[msg("Invalid virtual reserve offset")]
+    InvalidOffset,
+    #[msg("Curve does not allow deposits")]
+    DepositsNotAllowed,
+    #[msg("Invalid swap route")]
+    InvalidRoute,
+    #[msg("Flash loan not repaid with fee")]
+    FlashLoanNotRepaid,
+    #[msg("Flash swap input not paid")]
+    FlashSwapNotPaid,
+    #[msg("Oracle observation not available for the requested time")]
+    ObservationNotAvailable,
+    #[msg("Invalid number of oracle observations")]
+    InvalidObservationNum,
+    #[msg("Swap moves the price too far from its time weighted average")]
+    PriceDeviationExceeded,
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+    #[msg("Fee rates are invalid")]
+    InvalidFeeRate,
+    #[msg("An amm config update is already queued")]
+    PendingConfigUpdateExists,
+    #[msg("No amm config update is queued")]
+    NoPendingConfigUpdate,
+    #[msg("The queued amm config update is still timelocked")]
+    ConfigUpdateTimelocked,
+    #[msg("Trade fee rate plus creator fee rate must be below the fee rate denominator")]
+    TradeFeeRateTooHigh,
+    #[msg("Protocol fee rate plus fund fee rate exceeds the fee rate denominator")]
+    ProtocolFundFeeRateTooHigh,
+    #[msg("Min trade fee rate exceeds max trade fee rate")]
+    InvalidDynamicFeeRange,
+    #[msg("Flash fee rate must be below the fee rate denominator")]
+    FlashFeeRateTooHigh,
+    #[msg("Reduced token weights must sum to at most 7, e.g. 50/50, 60/40, 75/25 or 80/20")]
+    WeightSumTooHigh,
 }
@@ -6,10 +6,17 @@ use anchor_lang::prelude::*;
 pub struct ClosePermissionPda<'info> {
     #[account(
         mut,
-        address = crate::admin::ID @ ErrorCode::InvalidOwner
+        address = global_config.admin @ ErrorCode::InvalidOwner
     )]
     pub owner: Signer<'info>,
 
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
     /// CHECK: permission account authority
     pub permission_authority: UncheckedAccount<'info>,
 
@@ -8,9 +8,16 @@ pub struct CloseSupportMintAssociated<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        constraint = (owner.key() == crate::admin::ID || owner.key() == crate::create_support_mint_associated_owner::ID) @ ErrorCode::NotApproved
+        constraint = (owner.key() == global_config.admin || owner.key() == global_config.listing_manager) @ ErrorCode::NotApproved
     )]
     pub owner: Signer<'info>,
+
+    /// The global config holding the admin and listing manager
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     /// Support token mint
     #[account(
         owner = token_2022::ID @ ErrorCode::NotApproved
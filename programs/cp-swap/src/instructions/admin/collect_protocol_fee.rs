@@ -10,9 +10,16 @@ use anchor_spl::token_interface::TokenAccount;
 #[derive(Accounts)]
 pub struct CollectProtocolFee<'info> {
     /// Only admin or owner can collect fee now
-    #[account(constraint = (owner.key() == amm_config.protocol_owner || owner.key() == crate::admin::ID) @ ErrorCode::InvalidOwner)]
+    #[account(constraint = (owner.key() == amm_config.protocol_owner || owner.key() == global_config.admin) @ ErrorCode::InvalidOwner)]
     pub owner: Signer<'info>,
 
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
     /// CHECK: pool vault and lp mint authority
     #[account(
         seeds = [
@@ -9,10 +9,17 @@ pub struct CreateAmmConfig<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        address = crate::admin::ID @ ErrorCode::InvalidOwner
+        address = global_config.admin @ ErrorCode::InvalidOwner
     )]
     pub owner: Signer<'info>,
 
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
     /// Initialize config state account to store protocol owner address and fee rates.
     #[account(
         init,
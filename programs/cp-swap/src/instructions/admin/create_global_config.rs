@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+pub struct CreateGlobalConfig<'info> {
+    /// Only the deploy time admin can create the global config, and becomes its admin
+    #[account(
+        mut,
+        address = crate::admin::ID @ ErrorCode::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    /// Initialize global config state account to store the admin
+    #[account(
+        init,
+        seeds = [
+            GLOBAL_CONFIG_SEED.as_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = GlobalConfig::LEN
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_global_config(ctx: Context<CreateGlobalConfig>) -> Result<()> {
+    let global_config = ctx.accounts.global_config.deref_mut();
+    global_config.bump = ctx.bumps.global_config;
+    global_config.admin = ctx.accounts.owner.key();
+    global_config.pending_admin = Pubkey::default();
+    global_config.guardian = Pubkey::default();
+    global_config.fee_manager = Pubkey::default();
+    global_config.listing_manager = Pubkey::default();
+    global_config.status = 0;
+    Ok(())
+}
@@ -4,22 +4,21 @@ use anchor_lang::prelude::*;
 use anchor_spl::{token_2022, token_interface::Mint};
 use std::ops::DerefMut;
 
-pub mod create_support_mint_associated_owner {
-    use super::{pubkey, Pubkey};
-    #[cfg(feature = "devnet")]
-    pub const ID: Pubkey = pubkey!("DRaypyeDL6y1dUusMgwyeDM5JebjhsSi8aRXobKQ9DcQ");
-    #[cfg(not(feature = "devnet"))]
-    pub const ID: Pubkey = pubkey!("Rayv2LG4tFSMizZhMP8aSUYxDPjV8qJtx2NQY9RKYZy");
-}
-
 #[derive(Accounts)]
 pub struct CreateSupportMintAssociated<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        constraint = (owner.key() == crate::admin::ID || owner.key() == crate::create_support_mint_associated_owner::ID) @ ErrorCode::NotApproved
+        constraint = (owner.key() == global_config.admin || owner.key() == global_config.listing_manager) @ ErrorCode::NotApproved
     )]
     pub owner: Signer<'info>,
+
+    /// The global config holding the admin and listing manager
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
     /// Support token mint
     #[account(
         owner = token_2022::ID @ ErrorCode::NotApproved
@@ -0,0 +1,48 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigratePoolConfig<'info> {
+    #[account(
+        address = global_config.admin @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// The config the pool currently belongs to
+    #[account(address = pool_state.load()?.amm_config)]
+    pub old_amm_config: Account<'info, AmmConfig>,
+
+    /// The config the pool is moved to
+    #[account(constraint = new_amm_config.key() != old_amm_config.key() @ ErrorCode::InvalidInput)]
+    pub new_amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn migrate_pool_config(ctx: Context<MigratePoolConfig>) -> Result<()> {
+    let old_amm_config = &ctx.accounts.old_amm_config;
+    let new_amm_config = &ctx.accounts.new_amm_config;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.check_fee_rates(new_amm_config)?;
+
+    emit!(PoolConfigMigratedEvent {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_amm_config: old_amm_config.key(),
+        new_amm_config: new_amm_config.key(),
+        old_trade_fee_rate: pool_state.trade_fee_rate(old_amm_config),
+        new_trade_fee_rate: pool_state.trade_fee_rate(new_amm_config),
+    });
+
+    pool_state.amm_config = new_amm_config.key();
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
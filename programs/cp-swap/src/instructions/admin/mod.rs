@@ -1,12 +1,24 @@
+pub mod create_global_config;
+pub use create_global_config::*;
+
+pub mod transfer_admin;
+pub use transfer_admin::*;
+
 pub mod create_config;
 pub use create_config::*;
 
 pub mod update_config;
 pub use update_config::*;
 
+pub mod pending_config_update;
+pub use pending_config_update::*;
+
 pub mod update_pool_status;
 pub use update_pool_status::*;
 
+pub mod update_config_status;
+pub use update_config_status::*;
+
 pub mod collect_protocol_fee;
 pub use collect_protocol_fee::*;
 
@@ -24,3 +36,18 @@ pub use create_support_mint_associated::*;
 
 pub mod close_support_mint_associated;
 pub use close_support_mint_associated::*;
+
+pub mod ramp_amp;
+pub use ramp_amp::*;
+
+pub mod update_observation_duration;
+pub use update_observation_duration::*;
+
+pub mod update_price_deviation_limit;
+pub use update_price_deviation_limit::*;
+
+pub mod update_pool_fee_rate;
+pub use update_pool_fee_rate::*;
+
+pub mod migrate_pool_config;
+pub use migrate_pool_config::*;
@@ -0,0 +1,72 @@
+use super::update_config::{apply_amm_config_update, UpdateAmmConfig};
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExecuteAmmConfigUpdate<'info> {
+    /// Amm config account with the queued update
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn queue_amm_config_update(
+    ctx: Context<UpdateAmmConfig>,
+    update: AmmConfigUpdate,
+    activation_timestamp: u64,
+) -> Result<()> {
+    ctx.accounts.check_update_authority(&update)?;
+    let amm_config = &mut ctx.accounts.amm_config;
+    require!(
+        !amm_config.pending_update.is_queued(),
+        ErrorCode::PendingConfigUpdateExists
+    );
+    require_gte!(
+        activation_timestamp,
+        oracle::block_timestamp() + AMM_CONFIG_UPDATE_DELAY,
+        ErrorCode::InvalidInput
+    );
+
+    amm_config.pending_update = PendingConfigUpdate::new(update, activation_timestamp);
+    emit!(AmmConfigUpdateQueuedEvent {
+        amm_config: amm_config.key(),
+        update,
+        activation_timestamp,
+    });
+    Ok(())
+}
+
+pub fn execute_amm_config_update(ctx: Context<ExecuteAmmConfigUpdate>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let pending_update = amm_config.pending_update;
+    require!(pending_update.is_queued(), ErrorCode::NoPendingConfigUpdate);
+    require_gte!(
+        oracle::block_timestamp(),
+        pending_update.activation_timestamp,
+        ErrorCode::ConfigUpdateTimelocked
+    );
+    let update = pending_update.update()?;
+
+    amm_config.pending_update = PendingConfigUpdate::default();
+    apply_amm_config_update(amm_config, update)?;
+    emit!(AmmConfigUpdateExecutedEvent {
+        amm_config: amm_config.key(),
+        update,
+    });
+    Ok(())
+}
+
+pub fn cancel_amm_config_update(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+    let pending_update = ctx.accounts.amm_config.pending_update;
+    require!(pending_update.is_queued(), ErrorCode::NoPendingConfigUpdate);
+    let update = pending_update.update()?;
+    ctx.accounts.check_update_authority(&update)?;
+
+    let amm_config = &mut ctx.accounts.amm_config;
+    amm_config.pending_update = PendingConfigUpdate::default();
+    emit!(AmmConfigUpdateCancelledEvent {
+        amm_config: amm_config.key(),
+        update,
+    });
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::curve::{CurveType, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP, MIN_RAMP_DURATION};
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(
+        address = global_config.admin @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// The stable swap pool to be ramped
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, stop_ramp_ts: u64) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    if CurveType::from_u8(pool_state.curve_type)? != CurveType::StableSwap {
+        return err!(ErrorCode::InvalidCurveType);
+    }
+    if !(MIN_AMP..=MAX_AMP).contains(&target_amp) {
+        return err!(ErrorCode::InvalidAmp);
+    }
+    // A new ramp can only be started once the previous one lasted long enough
+    if block_timestamp < pool_state.start_ramp_ts.saturating_add(MIN_RAMP_DURATION)
+        || stop_ramp_ts < block_timestamp.saturating_add(MIN_RAMP_DURATION)
+    {
+        return err!(ErrorCode::InvalidAmpRamp);
+    }
+    let current_amp = pool_state.current_amp(block_timestamp);
+    if target_amp > current_amp.saturating_mul(MAX_AMP_CHANGE)
+        || target_amp.saturating_mul(MAX_AMP_CHANGE) < current_amp
+    {
+        return err!(ErrorCode::InvalidAmpRamp);
+    }
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "ramp amp, current_amp:{}, target_amp:{}, stop_ramp_ts:{}",
+        current_amp,
+        target_amp,
+        stop_ramp_ts
+    );
+    pool_state.initial_amp = current_amp;
+    pool_state.target_amp = target_amp;
+    pool_state.start_ramp_ts = block_timestamp;
+    pool_state.stop_ramp_ts = stop_ramp_ts;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
+
+pub fn stop_ramp_amp(ctx: Context<RampAmp>) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    if CurveType::from_u8(pool_state.curve_type)? != CurveType::StableSwap {
+        return err!(ErrorCode::InvalidCurveType);
+    }
+    let current_amp = pool_state.current_amp(block_timestamp);
+    pool_state.initial_amp = current_amp;
+    pool_state.target_amp = current_amp;
+    pool_state.start_ramp_ts = block_timestamp;
+    pool_state.stop_ramp_ts = block_timestamp;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
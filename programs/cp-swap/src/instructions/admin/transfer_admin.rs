@@ -0,0 +1,94 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account(address = global_config.admin @ ErrorCode::InvalidOwner)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(address = global_config.pending_admin @ ErrorCode::InvalidOwner)]
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRole<'info> {
+    #[account(address = global_config.admin @ ErrorCode::InvalidOwner)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Propose `new_admin` as admin, it takes over once it accepts.
+/// Proposing the default pubkey cancels a pending transfer.
+pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "global_config, admin:{}, pending_admin:{}",
+        global_config.admin.to_string(),
+        new_admin.to_string()
+    );
+    let old_pending_admin = global_config.pending_admin;
+    global_config.pending_admin = new_admin;
+    emit!(AdminTransferProposedEvent {
+        admin: global_config.admin,
+        old_pending_admin,
+        new_pending_admin: new_admin,
+    });
+    Ok(())
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    require_keys_neq!(global_config.pending_admin, Pubkey::default());
+    let old_admin = global_config.admin;
+    global_config.admin = global_config.pending_admin;
+    global_config.pending_admin = Pubkey::default();
+    emit!(AdminTransferredEvent {
+        old_admin,
+        new_admin: global_config.admin,
+    });
+    Ok(())
+}
+
+/// Grant `role` to `authority`, the default pubkey revokes it
+pub fn update_role(ctx: Context<UpdateRole>, role: GlobalRole, authority: Pubkey) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "global_config, role:{:?}, authority:{}",
+        role,
+        authority.to_string()
+    );
+    let old_authority = global_config.role_authority(role);
+    global_config.set_role(role, authority);
+    emit!(RoleUpdatedEvent {
+        role,
+        old_authority,
+        new_authority: authority,
+    });
+    Ok(())
+}
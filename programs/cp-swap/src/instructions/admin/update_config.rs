@@ -5,63 +5,156 @@ use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct UpdateAmmConfig<'info> {
-    /// The amm config owner or admin
-    #[account(address = crate::admin::ID @ ErrorCode::InvalidOwner)]
+    /// The admin, or the fee manager which can only update fee params
+    #[account(
+        constraint = (owner.key() == global_config.admin || owner.key() == global_config.fee_manager) @ ErrorCode::InvalidOwner
+    )]
     pub owner: Signer<'info>,
 
+    /// The global config holding the admin and fee manager
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
     /// Amm config account to be changed
     #[account(mut)]
     pub amm_config: Account<'info, AmmConfig>,
 }
 
-pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
-    let amm_config = &mut ctx.accounts.amm_config;
-    let match_param = Some(param);
-    match match_param {
-        Some(0) => update_trade_fee_rate(amm_config, value),
-        Some(1) => update_protocol_fee_rate(amm_config, value),
-        Some(2) => update_fund_fee_rate(amm_config, value),
-        Some(3) => {
-            let new_procotol_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
-            set_new_protocol_owner(amm_config, new_procotol_owner)?;
-        }
-        Some(4) => {
-            let new_fund_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
-            set_new_fund_owner(amm_config, new_fund_owner)?;
+impl UpdateAmmConfig<'_> {
+    /// The fee manager can only update the fees, everything else is left to the admin
+    pub fn check_update_authority(&self, update: &AmmConfigUpdate) -> Result<()> {
+        if self.owner.key() != self.global_config.admin {
+            require!(update.is_fee_update(), ErrorCode::InvalidOwner);
         }
-        Some(5) => amm_config.create_pool_fee = value,
-        Some(6) => amm_config.disable_create_pool = if value == 0 { false } else { true },
-        Some(7) => update_creator_fee_rate(amm_config, value),
-        _ => return err!(ErrorCode::InvalidInput),
+        Ok(())
+    }
+}
+
+pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, update: AmmConfigUpdate) -> Result<()> {
+    require!(
+        update.is_instant_update(),
+        ErrorCode::ConfigUpdateTimelocked
+    );
+    ctx.accounts.check_update_authority(&update)?;
+    apply_amm_config_update(&mut ctx.accounts.amm_config, update)
+}
+
+/// Validate and apply `update` to the config, emitting the old and new value
+pub(crate) fn apply_amm_config_update(
+    amm_config: &mut Account<AmmConfig>,
+    update: AmmConfigUpdate,
+) -> Result<()> {
+    let old_value = amm_config.current_value(update);
+    match update {
+        AmmConfigUpdate::TradeFeeRate(value) => update_trade_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::ProtocolFeeRate(value) => update_protocol_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::FundFeeRate(value) => update_fund_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::ProtocolOwner(new_owner) => set_new_protocol_owner(amm_config, new_owner)?,
+        AmmConfigUpdate::FundOwner(new_owner) => set_new_fund_owner(amm_config, new_owner)?,
+        AmmConfigUpdate::CreatePoolFee(value) => amm_config.create_pool_fee = value,
+        AmmConfigUpdate::DisableCreatePool(value) => amm_config.disable_create_pool = value,
+        AmmConfigUpdate::CreatorFeeRate(value) => update_creator_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::MinTradeFeeRate(value) => update_min_trade_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::MaxTradeFeeRate(value) => update_max_trade_fee_rate(amm_config, value)?,
+        AmmConfigUpdate::FlashFeeRate(value) => update_flash_fee_rate(amm_config, value)?,
     }
+    emit!(AmmConfigUpdated {
+        amm_config: amm_config.key(),
+        old_value,
+        new_value: update,
+    });
 
     Ok(())
 }
 
-fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u64) {
-    assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+fn update_protocol_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    protocol_fee_rate: u64,
+) -> Result<()> {
+    require!(
+        protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE
+            && protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::ProtocolFundFeeRateTooHigh
+    );
     amm_config.protocol_fee_rate = protocol_fee_rate;
+    Ok(())
 }
 
-fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) {
-    assert!(trade_fee_rate + amm_config.creator_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) -> Result<()> {
+    require!(
+        trade_fee_rate + amm_config.creator_fee_rate < FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::TradeFeeRateTooHigh
+    );
     amm_config.trade_fee_rate = trade_fee_rate;
+    Ok(())
 }
 
-fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) {
-    assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) -> Result<()> {
+    require!(
+        fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE
+            && fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::ProtocolFundFeeRateTooHigh
+    );
     amm_config.fund_fee_rate = fund_fee_rate;
+    Ok(())
 }
 
-fn update_creator_fee_rate(amm_config: &mut Account<AmmConfig>, creator_fee_rate: u64) {
-    assert!(creator_fee_rate + amm_config.trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
+fn update_creator_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    creator_fee_rate: u64,
+) -> Result<()> {
+    require!(
+        creator_fee_rate + amm_config.trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE
+            && creator_fee_rate + amm_config.max_trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::TradeFeeRateTooHigh
+    );
     amm_config.creator_fee_rate = creator_fee_rate;
+    Ok(())
+}
+
+fn update_min_trade_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    min_trade_fee_rate: u64,
+) -> Result<()> {
+    require!(
+        !amm_config.is_dynamic_fee_on() || min_trade_fee_rate <= amm_config.max_trade_fee_rate,
+        ErrorCode::InvalidDynamicFeeRange
+    );
+    amm_config.min_trade_fee_rate = min_trade_fee_rate;
+    Ok(())
+}
+
+/// Zero turns the dynamic trade fee off
+fn update_max_trade_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    max_trade_fee_rate: u64,
+) -> Result<()> {
+    require!(
+        max_trade_fee_rate == 0 || max_trade_fee_rate >= amm_config.min_trade_fee_rate,
+        ErrorCode::InvalidDynamicFeeRange
+    );
+    require!(
+        max_trade_fee_rate + amm_config.creator_fee_rate < FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::TradeFeeRateTooHigh
+    );
+    amm_config.max_trade_fee_rate = max_trade_fee_rate;
+    Ok(())
+}
+
+fn update_flash_fee_rate(amm_config: &mut Account<AmmConfig>, flash_fee_rate: u64) -> Result<()> {
+    require!(
+        flash_fee_rate < FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::FlashFeeRateTooHigh
+    );
+    amm_config.flash_fee_rate = flash_fee_rate;
+    Ok(())
 }
 
 fn set_new_protocol_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey) -> Result<()> {
-    require_keys_neq!(new_owner, Pubkey::default());
+    require_keys_neq!(new_owner, Pubkey::default(), ErrorCode::InvalidInput);
     #[cfg(feature = "enable-log")]
     msg!(
         "amm_config, old_protocol_owner:{}, new_owner:{}",
@@ -73,7 +166,7 @@ fn set_new_protocol_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey
 }
 
 fn set_new_fund_owner(amm_config: &mut Account<AmmConfig>, new_fund_owner: Pubkey) -> Result<()> {
-    require_keys_neq!(new_fund_owner, Pubkey::default());
+    require_keys_neq!(new_fund_owner, Pubkey::default(), ErrorCode::InvalidInput);
     #[cfg(feature = "enable-log")]
     msg!(
         "amm_config, old_fund_owner:{}, new_fund_owner:{}",
@@ -0,0 +1,74 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateAmmConfigStatus<'info> {
+    /// The admin, or the guardian which can only disable
+    #[account(
+        constraint = (authority.key() == global_config.admin || authority.key() == global_config.guardian) @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin and guardian
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// Amm config account whose pools are paused
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGlobalStatus<'info> {
+    /// The admin, or the guardian which can only disable
+    #[account(
+        constraint = (authority.key() == global_config.admin || authority.key() == global_config.guardian) @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn update_amm_config_status(ctx: Context<UpdateAmmConfigStatus>, status: u8) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    ctx.accounts.global_config.check_status_authority(
+        ctx.accounts.authority.key(),
+        amm_config.status,
+        status,
+    )?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amm_config, old_status:{}, new_status:{}",
+        amm_config.status,
+        status
+    );
+    amm_config.status = status;
+    Ok(())
+}
+
+pub fn update_global_status(ctx: Context<UpdateGlobalStatus>, status: u8) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.check_status_authority(
+        ctx.accounts.authority.key(),
+        global_config.status,
+        status,
+    )?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "global_config, old_status:{}, new_status:{}",
+        global_config.status,
+        status
+    );
+    global_config.status = status;
+    Ok(())
+}
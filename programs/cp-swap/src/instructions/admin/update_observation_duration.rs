@@ -0,0 +1,44 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateObservationDuration<'info> {
+    #[account(
+        address = global_config.admin @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn update_observation_duration(
+    ctx: Context<UpdateObservationDuration>,
+    update_duration: u64,
+) -> Result<()> {
+    require_gt!(update_duration, 0, ErrorCode::InvalidInput);
+    require_gte!(
+        OBSERVATION_UPDATE_DURATION_MAX,
+        update_duration,
+        ErrorCode::InvalidInput
+    );
+    let mut observation_state = ctx.accounts.observation_state.load_mut()?;
+    observation_state.update_duration = update_duration;
+    // The oracle must still cover the price deviation window of the pool
+    require_gte!(
+        observation_state.history_duration(),
+        ctx.accounts.pool_state.load()?.price_deviation_window,
+        ErrorCode::InvalidInput
+    );
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolFeeRate<'info> {
+    #[account(
+        address = global_config.admin @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    /// The config the pool fee rates fall back to
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_fee_rate(
+    ctx: Context<UpdatePoolFeeRate>,
+    update: PoolFeeRateUpdate,
+) -> Result<()> {
+    let amm_config = &ctx.accounts.amm_config;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let old_value = pool_state.current_fee_override(update);
+    match update {
+        PoolFeeRateUpdate::TradeFeeRate(value) => {
+            update_trade_fee_rate(&mut pool_state, amm_config, value)?
+        }
+        PoolFeeRateUpdate::ProtocolFeeRate(value) => {
+            update_protocol_fee_rate(&mut pool_state, amm_config, value)?
+        }
+        PoolFeeRateUpdate::FundFeeRate(value) => {
+            update_fund_fee_rate(&mut pool_state, amm_config, value)?
+        }
+    }
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    emit!(PoolFeeRateUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_value,
+        new_value: update,
+    });
+
+    Ok(())
+}
+
+fn update_trade_fee_rate(
+    pool_state: &mut PoolState,
+    amm_config: &AmmConfig,
+    value: Option<u64>,
+) -> Result<()> {
+    if let Some(trade_fee_rate) = value {
+        require!(
+            trade_fee_rate
+                .checked_add(amm_config.creator_fee_rate)
+                .is_some_and(|rate| rate < FEE_RATE_DENOMINATOR_VALUE),
+            ErrorCode::TradeFeeRateTooHigh
+        );
+    }
+    pool_state.set_fee_override(PoolFeeOverrideBitIndex::TradeFeeRate, value);
+    Ok(())
+}
+
+fn update_protocol_fee_rate(
+    pool_state: &mut PoolState,
+    amm_config: &AmmConfig,
+    value: Option<u64>,
+) -> Result<()> {
+    if let Some(protocol_fee_rate) = value {
+        require!(
+            protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE
+                && protocol_fee_rate
+                    .checked_add(pool_state.fund_fee_rate(amm_config))
+                    .is_some_and(|rate| rate <= FEE_RATE_DENOMINATOR_VALUE),
+            ErrorCode::ProtocolFundFeeRateTooHigh
+        );
+    }
+    pool_state.set_fee_override(PoolFeeOverrideBitIndex::ProtocolFeeRate, value);
+    Ok(())
+}
+
+fn update_fund_fee_rate(
+    pool_state: &mut PoolState,
+    amm_config: &AmmConfig,
+    value: Option<u64>,
+) -> Result<()> {
+    if let Some(fund_fee_rate) = value {
+        require!(
+            fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE
+                && fund_fee_rate
+                    .checked_add(pool_state.protocol_fee_rate(amm_config))
+                    .is_some_and(|rate| rate <= FEE_RATE_DENOMINATOR_VALUE),
+            ErrorCode::ProtocolFundFeeRateTooHigh
+        );
+    }
+    pool_state.set_fee_override(PoolFeeOverrideBitIndex::FundFeeRate, value);
+    Ok(())
+}
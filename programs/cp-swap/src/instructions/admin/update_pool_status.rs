@@ -1,13 +1,22 @@
+use crate::error::ErrorCode;
 use crate::states::*;
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct UpdatePoolStatus<'info> {
+    /// The admin, or the guardian which can only disable
     #[account(
-        address = crate::admin::ID
+        constraint = (authority.key() == global_config.admin || authority.key() == global_config.guardian) @ ErrorCode::InvalidOwner
     )]
     pub authority: Signer<'info>,
 
+    /// The global config holding the admin and guardian
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
     #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
 }
@@ -15,6 +24,11 @@ pub struct UpdatePoolStatus<'info> {
 pub fn update_pool_status(ctx: Context<UpdatePoolStatus>, status: u8) -> Result<()> {
     require_gte!(255, status);
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    ctx.accounts.global_config.check_status_authority(
+        ctx.accounts.authority.key(),
+        pool_state.status,
+        status,
+    )?;
     pool_state.set_status(status);
     pool_state.recent_epoch = Clock::get()?.epoch;
     Ok(())
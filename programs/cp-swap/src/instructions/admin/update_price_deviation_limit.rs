@@ -0,0 +1,51 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePriceDeviationLimit<'info> {
+    #[account(
+        address = global_config.admin @ ErrorCode::InvalidOwner
+    )]
+    pub authority: Signer<'info>,
+
+    /// The global config holding the admin
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle the time weighted average price is read from
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn update_price_deviation_limit(
+    ctx: Context<UpdatePriceDeviationLimit>,
+    max_price_deviation_bps: u64,
+    price_deviation_window: u64,
+) -> Result<()> {
+    require_gte!(
+        BPS_DENOMINATOR,
+        max_price_deviation_bps,
+        ErrorCode::InvalidInput
+    );
+    if max_price_deviation_bps != 0 {
+        require_gt!(price_deviation_window, 0, ErrorCode::InvalidInput);
+        // A longer window would be cut to the observations the oracle keeps
+        require_gte!(
+            ctx.accounts.observation_state.load()?.history_duration(),
+            price_deviation_window,
+            ErrorCode::InvalidInput
+        );
+    }
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.max_price_deviation_bps = max_price_deviation_bps;
+    pool_state.price_deviation_window = price_deviation_window;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct ConsultTwap<'info> {
+    /// Pool state account
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn consult_twap(ctx: Context<ConsultTwap>, window: u64) -> Result<()> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    // The current prices extend the cumulative prices since the last observation
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+        oracle::block_timestamp(),
+    )?;
+    let (token_0_price_x32, token_1_price_x32) =
+        oracle::load_observations(&ctx.accounts.observation_state)?.consult_twap(
+            oracle::block_timestamp(),
+            window,
+            token_0_price_x32,
+            token_1_price_x32,
+        )?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "window:{}, token_0_price_x32:{}, token_1_price_x32:{}",
+        window,
+        token_0_price_x32,
+        token_1_price_x32
+    );
+
+    let mut data = Vec::new();
+    TwapPrice {
+        token_0_price_x32,
+        token_1_price_x32,
+    }
+    .serialize(&mut data)?;
+    set_return_data(&data);
+
+    Ok(())
+}
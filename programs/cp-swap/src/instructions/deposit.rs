@@ -82,6 +82,21 @@ pub struct Deposit<'info> {
         address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint)
     ]
     pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The amm config holding the status of its pools
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 pub fn deposit(
@@ -93,14 +108,28 @@ pub fn deposit(
     require_gt!(lp_token_amount, 0);
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
-    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Deposit,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) {
         return err!(ErrorCode::NotApproved);
     }
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     )?;
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+        oracle::block_timestamp(),
+    )?;
+    let curve = pool_state.curve(oracle::block_timestamp())?;
+    if !curve.allows_deposits() {
+        return err!(ErrorCode::DepositsNotAllowed);
+    }
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        curve.as_ref(),
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -199,6 +228,12 @@ pub fn deposit(
         lp_token_amount,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+    )?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
@@ -0,0 +1,160 @@
+use super::deposit::Deposit;
+use crate::curve::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+
+pub fn deposit_by_amounts(
+    ctx: Context<Deposit>,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    minimum_lp_out: u64,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Deposit,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+        oracle::block_timestamp(),
+    )?;
+    let curve = pool_state.curve(oracle::block_timestamp())?;
+    if !curve.allows_deposits() {
+        return err!(ErrorCode::DepositsNotAllowed);
+    }
+
+    // The maximums are what leaves the owner, only the amounts net of transfer fees reach the vaults
+    let maximum_token_0_received = maximum_token_0_amount.saturating_sub(get_transfer_fee(
+        &ctx.accounts.vault_0_mint.to_account_info(),
+        maximum_token_0_amount,
+    )?);
+    let maximum_token_1_received = maximum_token_1_amount.saturating_sub(get_transfer_fee(
+        &ctx.accounts.vault_1_mint.to_account_info(),
+        maximum_token_1_amount,
+    )?);
+    let (lp_token_amount, results) = CurveCalculator::trading_tokens_to_lp_tokens(
+        curve.as_ref(),
+        u128::from(maximum_token_0_received),
+        u128::from(maximum_token_1_received),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_token_0_amount),
+        u128::from(total_token_1_amount),
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    if lp_token_amount == 0 || results.token_0_amount == 0 || results.token_1_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    let lp_token_amount = u64::try_from(lp_token_amount).unwrap();
+    require_gte!(lp_token_amount, minimum_lp_out, ErrorCode::ExceededSlippage);
+
+    let token_0_amount = u64::try_from(results.token_0_amount).unwrap();
+    let (transfer_token_0_amount, transfer_token_0_fee) = {
+        let transfer_fee =
+            get_transfer_inverse_fee(&ctx.accounts.vault_0_mint.to_account_info(), token_0_amount)?;
+        (
+            token_0_amount.checked_add(transfer_fee).unwrap(),
+            transfer_fee,
+        )
+    };
+
+    let token_1_amount = u64::try_from(results.token_1_amount).unwrap();
+    let (transfer_token_1_amount, transfer_token_1_fee) = {
+        let transfer_fee =
+            get_transfer_inverse_fee(&ctx.accounts.vault_1_mint.to_account_info(), token_1_amount)?;
+        (
+            token_1_amount.checked_add(transfer_fee).unwrap(),
+            transfer_fee,
+        )
+    };
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "lp_token_amount:{}, results.token_0_amount;{}, results.token_1_amount:{},transfer_token_0_amount:{},transfer_token_0_fee:{},
+            transfer_token_1_amount:{},transfer_token_1_fee:{}",
+        lp_token_amount,
+        results.token_0_amount,
+        results.token_1_amount,
+        transfer_token_0_amount,
+        transfer_token_0_fee,
+        transfer_token_1_amount,
+        transfer_token_1_fee
+    );
+
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: total_token_0_amount,
+        token_1_vault_before: total_token_1_amount,
+        token_0_amount,
+        token_1_amount,
+        token_0_transfer_fee: transfer_token_0_fee,
+        token_1_transfer_fee: transfer_token_1_fee,
+        change_type: 0
+    });
+
+    // The inverse fee may round up past the fee taken from the maximum
+    if transfer_token_0_amount > maximum_token_0_amount
+        || transfer_token_1_amount > maximum_token_1_amount
+    {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        transfer_token_0_amount,
+        ctx.accounts.vault_0_mint.decimals,
+    )?;
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        transfer_token_1_amount,
+        ctx.accounts.vault_1_mint.decimals,
+    )?;
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+
+    token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
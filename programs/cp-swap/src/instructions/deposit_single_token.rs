@@ -0,0 +1,271 @@
+use crate::curve::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct DepositSingleToken<'info> {
+    /// Pays to mint the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read trade fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Owner lp token account
+    #[account(mut, token::authority = owner)]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account for the deposited token
+    #[account(
+        mut,
+        token::mint = input_vault.mint,
+        token::authority = owner
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for the deposited token
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.load()?.token_0_vault || input_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for the other token
+    #[account(
+        constraint = output_vault.key() == pool_state.load()?.token_0_vault || output_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program for the deposited token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of the deposited token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Lp token mint
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint)
+    ]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn deposit_single_token(
+    ctx: Context<DepositSingleToken>,
+    amount_in: u64,
+    minimum_lp_out: u64,
+) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // Part of the deposit is swapped, so the pool has to be open for both
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Deposit,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Swap,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    let SwapParams {
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x64,
+        token_1_price_x64,
+        is_creator_fee_on_input,
+    } = pool_state.get_swap_params(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+        block_timestamp,
+    )?;
+    let curve = pool_state.curve(block_timestamp)?;
+    if !curve.allows_deposits() {
+        return err!(ErrorCode::DepositsNotAllowed);
+    }
+    let (total_token_0_amount, total_token_1_amount) = trade_direction.to_token_amounts(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+    );
+    let constant_before = curve
+        .invariant(total_token_0_amount, total_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        pool_state.trade_fee_rate(&ctx.accounts.amm_config),
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        token_0_price_x64,
+    );
+    let fee_rates = pool_state.fee_rates(
+        &ctx.accounts.amm_config,
+        trade_fee_rate,
+        is_creator_fee_on_input,
+    )?;
+    let result = CurveCalculator::deposit_single_token(
+        curve.as_ref(),
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        u128::from(pool_state.lp_supply),
+        trade_direction,
+        fee_rates,
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let swap = &result.swap;
+
+    let (new_token_0_amount, new_token_1_amount) =
+        trade_direction.to_token_amounts(swap.new_input_vault_amount, swap.new_output_vault_amount);
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "swap input_amount:{}, swap output_amount:{}, trade_fee:{}, deposit_input_amount:{}, lp_token_amount:{}, input_transfer_fee:{}, constant_before:{}, constant_after:{}",
+        swap.input_amount,
+        swap.output_amount,
+        swap.trade_fee,
+        result.deposit_input_amount,
+        result.lp_token_amount,
+        transfer_fee,
+        constant_before,
+        constant_after
+    );
+    require_gte!(constant_after, constant_before);
+    pool_state.check_price_deviation(
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        block_timestamp,
+        token_0_price_x64,
+        token_1_price_x64,
+        new_token_0_amount,
+        new_token_1_amount,
+    )?;
+
+    let lp_token_amount = u64::try_from(result.lp_token_amount).unwrap();
+    if lp_token_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    require_gte!(lp_token_amount, minimum_lp_out, ErrorCode::ExceededSlippage);
+
+    pool_state.update_fees(
+        u64::try_from(swap.protocol_fee).unwrap(),
+        u64::try_from(swap.fund_fee).unwrap(),
+        u64::try_from(swap.creator_fee).unwrap(),
+        trade_direction,
+    )?;
+
+    // The swapped tokens never leave the pool, they are deposited right away
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_amount: u64::try_from(swap.input_amount).unwrap(),
+        output_amount: u64::try_from(swap.output_amount).unwrap(),
+        input_transfer_fee: transfer_fee,
+        output_transfer_fee: 0,
+        base_input: true,
+        input_mint: ctx.accounts.input_vault.mint,
+        output_mint: ctx.accounts.output_vault.mint,
+        trade_fee: u64::try_from(swap.trade_fee).unwrap(),
+        creator_fee: u64::try_from(swap.creator_fee).unwrap(),
+        creator_fee_on_input: is_creator_fee_on_input,
+        trade_fee_rate,
+        unfilled_amount: 0,
+    });
+
+    let deposit_input_amount = u64::try_from(result.deposit_input_amount).unwrap();
+    let deposit_output_amount = u64::try_from(swap.output_amount).unwrap();
+    let (token_0_amount, token_1_amount) = trade_direction.to_token_amounts(
+        u128::from(deposit_input_amount),
+        u128::from(deposit_output_amount),
+    );
+    let (token_0_transfer_fee, token_1_transfer_fee) =
+        trade_direction.to_token_amounts(u128::from(transfer_fee), 0);
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: u64::try_from(total_token_0_amount).unwrap(),
+        token_1_vault_before: u64::try_from(total_token_1_amount).unwrap(),
+        token_0_amount: u64::try_from(token_0_amount).unwrap(),
+        token_1_amount: u64::try_from(token_1_amount).unwrap(),
+        token_0_transfer_fee: u64::try_from(token_0_transfer_fee).unwrap(),
+        token_1_transfer_fee: u64::try_from(token_1_transfer_fee).unwrap(),
+        change_type: 0
+    });
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        amount_in,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_add(lp_token_amount).unwrap();
+
+    token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x64,
+        token_1_price_x64,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use crate::curve::{Fees, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// The borrower program is called with the discriminator of an anchor instruction
+/// named `on_flash_loan`, sha256("global:on_flash_loan")[..8], followed by
+/// amount_0, amount_1, flash_fee_0 and flash_fee_1
+pub const FLASH_LOAN_CALLBACK_DISCRIMINATOR: [u8; 8] = [195, 212, 238, 236, 80, 204, 73, 167];
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    /// The user taking the loan
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read flash loan fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The token account for receive the borrowed token_0
+    #[account(
+        mut,
+        token::mint = token_0_vault.mint,
+    )]
+    pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account for receive the borrowed token_1
+    #[account(
+        mut,
+        token::mint = token_1_vault.mint,
+    )]
+    pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the program called back with the loan, it must repay the vaults before returning
+    #[account(
+        executable,
+        constraint = borrower_program.key() != crate::id() @ ErrorCode::InvalidInput
+    )]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn flash_loan<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, FlashLoan<'info>>,
+    amount_0: u64,
+    amount_1: u64,
+) -> Result<()> {
+    if amount_0 == 0 && amount_1 == 0 {
+        return err!(ErrorCode::InvalidInput);
+    }
+    let pool_id = ctx.accounts.pool_state.key();
+    // Solana rejects reentrant calls into this program, so the borrower can
+    // not trade against or change the liquidity of the pool during the callback
+    let (auth_bump, total_token_0_amount, total_token_1_amount) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit_with_configs(
+            PoolStatusBitIndex::Swap,
+            &ctx.accounts.amm_config,
+            &ctx.accounts.global_config,
+        ) || oracle::block_timestamp() < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        )?;
+        (
+            pool_state.auth_bump,
+            total_token_0_amount,
+            total_token_1_amount,
+        )
+    };
+
+    let amm_config = &ctx.accounts.amm_config;
+    let flash_fee = |amount: u64| -> Result<u64> {
+        let flash_fee = Fees::flash_fee(u128::from(amount), amm_config.flash_fee_rate)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(u64::try_from(flash_fee).map_err(|_| ErrorCode::MathOverflow)?)
+    };
+    let flash_fee_0 = flash_fee(amount_0)?;
+    let flash_fee_1 = flash_fee(amount_1)?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_0,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_1,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    let mut data = FLASH_LOAN_CALLBACK_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_0.to_le_bytes());
+    data.extend_from_slice(&amount_1.to_le_bytes());
+    data.extend_from_slice(&flash_fee_0.to_le_bytes());
+    data.extend_from_slice(&flash_fee_1.to_le_bytes());
+    invoke_borrower(
+        &ctx.accounts.borrower_program.to_account_info(),
+        ctx.remaining_accounts,
+        data,
+    )?;
+
+    ctx.accounts.token_0_vault.reload()?;
+    ctx.accounts.token_1_vault.reload()?;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    // The flash fee is split like the trade fee, what is left of it goes to lps
+    let protocol_fee_rate = pool_state.protocol_fee_rate(amm_config);
+    let fund_fee_rate = pool_state.fund_fee_rate(amm_config);
+    let split_fee = |flash_fee: u64| -> Result<(u64, u64)> {
+        let protocol_fee = Fees::protocol_fee(u128::from(flash_fee), protocol_fee_rate)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let fund_fee =
+            Fees::fund_fee(u128::from(flash_fee), fund_fee_rate).ok_or(ErrorCode::MathOverflow)?;
+        Ok((
+            u64::try_from(protocol_fee).map_err(|_| ErrorCode::MathOverflow)?,
+            u64::try_from(fund_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        ))
+    };
+    let (protocol_fee_0, fund_fee_0) = split_fee(flash_fee_0)?;
+    let (protocol_fee_1, fund_fee_1) = split_fee(flash_fee_1)?;
+    pool_state.update_fees(protocol_fee_0, fund_fee_0, 0, TradeDirection::ZeroForOne)?;
+    pool_state.update_fees(protocol_fee_1, fund_fee_1, 0, TradeDirection::OneForZero)?;
+
+    let (new_total_token_0_amount, new_total_token_1_amount) = pool_state
+        .vault_amount_without_fee(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        )
+        .map_err(|_| error!(ErrorCode::FlashLoanNotRepaid))?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_0:{}, amount_1:{}, flash_fee_0:{}, flash_fee_1:{}, total_token_0_amount:{}, total_token_1_amount:{}, new_total_token_0_amount:{}, new_total_token_1_amount:{}",
+        amount_0,
+        amount_1,
+        flash_fee_0,
+        flash_fee_1,
+        total_token_0_amount,
+        total_token_1_amount,
+        new_total_token_0_amount,
+        new_total_token_1_amount
+    );
+    // The lps keep what is left of the flash fee after the protocol and fund fees
+    let repaid_amount = |total_amount: u64, flash_fee: u64, protocol_fee: u64, fund_fee: u64| {
+        total_amount
+            .checked_add(flash_fee)
+            .and_then(|amount| amount.checked_sub(protocol_fee))
+            .and_then(|amount| amount.checked_sub(fund_fee))
+            .ok_or(ErrorCode::MathOverflow)
+    };
+    if new_total_token_0_amount
+        < repaid_amount(
+            total_token_0_amount,
+            flash_fee_0,
+            protocol_fee_0,
+            fund_fee_0,
+        )?
+        || new_total_token_1_amount
+            < repaid_amount(
+                total_token_1_amount,
+                flash_fee_1,
+                protocol_fee_1,
+                fund_fee_1,
+            )?
+    {
+        return err!(ErrorCode::FlashLoanNotRepaid);
+    }
+
+    emit!(FlashLoanEvent {
+        pool_id,
+        borrower_program: ctx.accounts.borrower_program.key(),
+        amount_0,
+        amount_1,
+        flash_fee_0,
+        flash_fee_1,
+    });
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
+
+/// Call back the borrower program, passing the remaining accounts through as they are
+pub(crate) fn invoke_borrower<'info>(
+    borrower_program: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+) -> Result<()> {
+    let accounts = remaining_accounts
+        .iter()
+        .map(|account| {
+            if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            }
+        })
+        .collect();
+    let mut account_infos = remaining_accounts.to_vec();
+    account_infos.push(borrower_program.clone());
+    invoke(
+        &Instruction {
+            program_id: borrower_program.key(),
+            accounts,
+            data,
+        },
+        &account_infos,
+    )?;
+    Ok(())
+}
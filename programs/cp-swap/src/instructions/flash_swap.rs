@@ -0,0 +1,316 @@
+use super::flash_loan::invoke_borrower;
+use crate::curve::calculator::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// The borrower program is called with the discriminator of an anchor instruction
+/// named `on_flash_swap`, sha256("global:on_flash_swap")[..8], followed by
+/// amount_out and input_transfer_amount, the amount to pay into the input vault
+/// including its transfer fee
+pub const FLASH_SWAP_CALLBACK_DISCRIMINATOR: [u8; 8] = [231, 227, 105, 50, 75, 86, 102, 148];
+
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool in which the swap will be performed
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The user token account for output token
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.load()?.token_0_vault || input_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.load()?.token_0_vault || output_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for output token transfers
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// CHECK: the program called back with the output, it must pay the input before returning
+    #[account(
+        executable,
+        constraint = borrower_program.key() != crate::id() @ ErrorCode::InvalidInput
+    )]
+    pub borrower_program: UncheckedAccount<'info>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+    max_amount_in: u64,
+    amount_out_received: u64,
+) -> Result<()> {
+    require_gt!(amount_out_received, 0);
+    let block_timestamp = oracle::block_timestamp();
+    let pool_id = ctx.accounts.pool_state.key();
+    let out_transfer_fee = get_transfer_inverse_fee(
+        &ctx.accounts.output_token_mint.to_account_info(),
+        amount_out_received,
+    )?;
+    let amount_out_with_transfer_fee = amount_out_received
+        .checked_add(out_transfer_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let input_vault_amount_before = ctx.accounts.input_vault.amount;
+
+    // Solana rejects reentrant calls into this program, so the borrower can
+    // not trade against or change the liquidity of the pool during the callback
+    let (
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x64,
+        token_1_price_x64,
+        is_creator_fee_on_input,
+        constant_before,
+        trade_fee_rate,
+        result,
+        auth_bump,
+    ) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit_with_configs(
+            PoolStatusBitIndex::Swap,
+            &ctx.accounts.amm_config,
+            &ctx.accounts.global_config,
+        ) || block_timestamp < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        let SwapParams {
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x64,
+            token_1_price_x64,
+            is_creator_fee_on_input,
+        } = pool_state.get_swap_params(
+            ctx.accounts.input_vault.key(),
+            ctx.accounts.output_vault.key(),
+            ctx.accounts.input_vault.amount,
+            ctx.accounts.output_vault.amount,
+            block_timestamp,
+        )?;
+        let curve = pool_state.curve(block_timestamp)?;
+        let (total_token_0_amount, total_token_1_amount) = trade_direction.to_token_amounts(
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+        );
+        let constant_before = curve
+            .invariant(total_token_0_amount, total_token_1_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+            pool_state.trade_fee_rate(&ctx.accounts.amm_config),
+            &oracle::load_observations(&ctx.accounts.observation_state)?,
+            token_0_price_x64,
+        );
+        let fee_rates = pool_state.fee_rates(
+            &ctx.accounts.amm_config,
+            trade_fee_rate,
+            is_creator_fee_on_input,
+        )?;
+        let result = CurveCalculator::swap_base_output(
+            curve.as_ref(),
+            u128::from(amount_out_with_transfer_fee),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            fee_rates,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+        (
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x64,
+            token_1_price_x64,
+            is_creator_fee_on_input,
+            constant_before,
+            trade_fee_rate,
+            result,
+            pool_state.auth_bump,
+        )
+    };
+
+    let input_amount = u64::try_from(result.input_amount).map_err(|_| ErrorCode::MathOverflow)?;
+    require_gt!(input_amount, 0);
+    let input_transfer_fee = get_transfer_inverse_fee(
+        &ctx.accounts.input_token_mint.to_account_info(),
+        input_amount,
+    )?;
+    let input_transfer_amount = input_amount
+        .checked_add(input_transfer_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require_gte!(
+        max_amount_in,
+        input_transfer_amount,
+        ErrorCode::ExceededSlippage
+    );
+    require_eq!(
+        u64::try_from(result.output_amount).map_err(|_| ErrorCode::MathOverflow)?,
+        amount_out_with_transfer_fee
+    );
+
+    // Send the output first, the input is only checked once the borrower returns
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        amount_out_with_transfer_fee,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    let mut data = FLASH_SWAP_CALLBACK_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_out_received.to_le_bytes());
+    data.extend_from_slice(&input_transfer_amount.to_le_bytes());
+    invoke_borrower(
+        &ctx.accounts.borrower_program.to_account_info(),
+        ctx.remaining_accounts,
+        data,
+    )?;
+
+    ctx.accounts.input_vault.reload()?;
+    ctx.accounts.output_vault.reload()?;
+    // The input must cover the fees as well, not just keep the constant
+    require_gte!(
+        ctx.accounts
+            .input_vault
+            .amount
+            .saturating_sub(input_vault_amount_before),
+        input_amount,
+        ErrorCode::FlashSwapNotPaid
+    );
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.update_fees(
+        u64::try_from(result.protocol_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        u64::try_from(result.fund_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        u64::try_from(result.creator_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        trade_direction,
+    )?;
+
+    // The same constant product check as a regular swap, but against what actually
+    // arrived in the vaults
+    let SwapParams {
+        total_input_token_amount: new_total_input_token_amount,
+        total_output_token_amount: new_total_output_token_amount,
+        ..
+    } = pool_state
+        .get_swap_params(
+            ctx.accounts.input_vault.key(),
+            ctx.accounts.output_vault.key(),
+            ctx.accounts.input_vault.amount,
+            ctx.accounts.output_vault.amount,
+            block_timestamp,
+        )
+        .map_err(|_| error!(ErrorCode::FlashSwapNotPaid))?;
+    let (new_token_0_amount, new_token_1_amount) = trade_direction.to_token_amounts(
+        u128::from(new_total_input_token_amount),
+        u128::from(new_total_output_token_amount),
+    );
+    let constant_after = pool_state
+        .curve(block_timestamp)?
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "input_amount:{}, output_amount:{}, trade_fee:{}, output_transfer_fee:{}, constant_before:{}, constant_after:{}, is_creator_fee_on_input:{}, creator_fee:{}",
+        result.input_amount,
+        result.output_amount,
+        result.trade_fee,
+        out_transfer_fee,
+        constant_before,
+        constant_after,
+        is_creator_fee_on_input,
+        result.creator_fee,
+    );
+    require_gte!(constant_after, constant_before, ErrorCode::FlashSwapNotPaid);
+    pool_state.check_price_deviation(
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        block_timestamp,
+        token_0_price_x64,
+        token_1_price_x64,
+        new_token_0_amount,
+        new_token_1_amount,
+    )?;
+
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_amount,
+        output_amount: amount_out_with_transfer_fee,
+        input_transfer_fee,
+        output_transfer_fee: out_transfer_fee,
+        base_input: false,
+        input_mint: ctx.accounts.input_token_mint.key(),
+        output_mint: ctx.accounts.output_token_mint.key(),
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        creator_fee: u64::try_from(result.creator_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        creator_fee_on_input: is_creator_fee_on_input,
+        trade_fee_rate,
+        unfilled_amount: 0,
+    });
+
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x64,
+        token_1_price_x64,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(observation_num: u16)]
+pub struct IncreaseObservationNum<'info> {
+    /// Pays the rent of the larger observation account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Pool state account
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(
+        mut,
+        address = pool_state.load()?.observation_key,
+        realloc = ObservationState::space(usize::from(observation_num)),
+        realloc::payer = payer,
+        realloc::zero = true,
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn increase_observation_num(
+    ctx: Context<IncreaseObservationNum>,
+    observation_num: u16,
+) -> Result<()> {
+    let observation_num = usize::from(observation_num);
+    // The account is already resized, its observation_num is still the old one
+    let current_observation_num = ctx.accounts.observation_state.load()?.observation_num();
+    require_gt!(
+        observation_num,
+        current_observation_num,
+        ErrorCode::InvalidObservationNum
+    );
+    require_gte!(
+        OBSERVATION_NUM_MAX,
+        observation_num,
+        ErrorCode::InvalidObservationNum
+    );
+
+    let mut observations = oracle::load_observations_mut(&ctx.accounts.observation_state)?;
+    // The new observations are written after the most recent one, so it must be the last
+    observations.linearize();
+    observations.state.observation_num = observation_num as u64;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "observation_num:{}, current_observation_num:{}",
+        observation_num,
+        current_observation_num
+    );
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::curve::CurveCalculator;
+use crate::curve::{CurveCalculator, CurveParams};
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::*;
@@ -184,7 +184,9 @@ pub fn initialize(
     init_amount_0: u64,
     init_amount_1: u64,
     mut open_time: u64,
+    curve_params: CurveParams,
 ) -> Result<()> {
+    curve_params.validate()?;
     let mint0_associated_is_initialized = support_mint_associated_is_initialized(
         &ctx.remaining_accounts,
         &ctx.accounts.token_0_mint,
@@ -289,13 +291,23 @@ pub fn initialize(
         )?
         .base;
 
-    CurveCalculator::validate_supply(token_0_vault.amount, token_1_vault.amount)?;
+    // An offset pool can be seeded with token_0 only, its virtual reserves stand in for token_1
+    CurveCalculator::validate_supply(
+        token_0_vault.amount,
+        token_1_vault
+            .amount
+            .checked_add(curve_params.token_1_offset())
+            .ok_or(ErrorCode::MathOverflow)?,
+    )?;
 
-    let liquidity = U128::from(token_0_vault.amount)
-        .checked_mul(token_1_vault.amount.into())
-        .unwrap()
-        .integer_sqrt()
-        .as_u64();
+    let liquidity = curve_params
+        .curve()
+        .initial_liquidity(
+            u128::from(token_0_vault.amount),
+            u128::from(token_1_vault.amount),
+        )
+        .and_then(|liquidity| u64::try_from(liquidity).ok())
+        .ok_or(ErrorCode::MathOverflow)?;
     let lock_lp_amount = 100;
     msg!(
         "liquidity:{}, lock_lp_amount:{}, vault_0_amount:{},vault_1_amount:{}",
@@ -342,20 +354,22 @@ pub fn initialize(
     }
 
     pool_state.initialize(
-        ctx.bumps.authority,
+        PoolAccounts {
+            auth_bump: ctx.bumps.authority,
+            pool_creator: ctx.accounts.creator.key(),
+            amm_config: ctx.accounts.amm_config.key(),
+            token_0_vault: ctx.accounts.token_0_vault.key(),
+            token_1_vault: ctx.accounts.token_1_vault.key(),
+            token_0_mint: &ctx.accounts.token_0_mint,
+            token_1_mint: &ctx.accounts.token_1_mint,
+            lp_mint: &ctx.accounts.lp_mint,
+            observation_key: ctx.accounts.observation_state.key(),
+        },
         liquidity,
         open_time,
-        ctx.accounts.creator.key(),
-        ctx.accounts.amm_config.key(),
-        ctx.accounts.token_0_vault.key(),
-        ctx.accounts.token_1_vault.key(),
-        &ctx.accounts.token_0_mint,
-        &ctx.accounts.token_1_mint,
-        ctx.accounts.lp_mint.key(),
-        ctx.accounts.lp_mint.decimals,
-        ctx.accounts.observation_state.key(),
         CreatorFeeOn::BothToken,
         false,
+        curve_params,
     );
 
     Ok(())
@@ -1,4 +1,4 @@
-use crate::curve::CurveCalculator;
+use crate::curve::{CurveCalculator, CurveParams};
 use crate::error::ErrorCode;
 use crate::initialize::create_pool;
 use crate::states::*;
@@ -355,20 +355,22 @@ pub fn initialize_with_permission(
     }
 
     pool_state.initialize(
-        ctx.bumps.authority,
+        PoolAccounts {
+            auth_bump: ctx.bumps.authority,
+            pool_creator: ctx.accounts.creator.key(),
+            amm_config: ctx.accounts.amm_config.key(),
+            token_0_vault: ctx.accounts.token_0_vault.key(),
+            token_1_vault: ctx.accounts.token_1_vault.key(),
+            token_0_mint: &ctx.accounts.token_0_mint,
+            token_1_mint: &ctx.accounts.token_1_mint,
+            lp_mint: &ctx.accounts.lp_mint,
+            observation_key: ctx.accounts.observation_state.key(),
+        },
         liquidity,
         open_time,
-        ctx.accounts.creator.key(),
-        ctx.accounts.amm_config.key(),
-        ctx.accounts.token_0_vault.key(),
-        ctx.accounts.token_1_vault.key(),
-        &ctx.accounts.token_0_mint,
-        &ctx.accounts.token_1_mint,
-        ctx.accounts.lp_mint.key(),
-        ctx.accounts.lp_mint.decimals,
-        ctx.accounts.observation_state.key(),
         creator_fee_on,
         true,
+        CurveParams::ConstantProduct,
     );
 
     Ok(())
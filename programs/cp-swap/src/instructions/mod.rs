@@ -1,12 +1,20 @@
 pub mod deposit;
+pub mod deposit_by_amounts;
+pub mod deposit_single_token;
 pub mod initialize;
 pub mod swap_base_input;
 pub mod withdraw;
+pub mod withdraw_by_amounts;
+pub mod withdraw_single_token;
 
 pub use deposit::*;
+pub use deposit_by_amounts::*;
+pub use deposit_single_token::*;
 pub use initialize::*;
 pub use swap_base_input::*;
 pub use withdraw::*;
+pub use withdraw_by_amounts::*;
+pub use withdraw_single_token::*;
 
 pub mod admin;
 pub use admin::*;
@@ -14,6 +22,30 @@ pub use admin::*;
 pub mod swap_base_output;
 pub use swap_base_output::*;
 
+pub mod swap_base_input_with_price_limit;
+pub use swap_base_input_with_price_limit::*;
+
+pub mod with_deadline;
+pub use with_deadline::*;
+
+pub mod swap_route;
+pub use swap_route::*;
+
+pub mod flash_loan;
+pub use flash_loan::*;
+
+pub mod flash_swap;
+pub use flash_swap::*;
+
+pub mod consult_twap;
+pub use consult_twap::*;
+
+pub mod increase_observation_num;
+pub use increase_observation_num::*;
+
+pub mod update_observation;
+pub use update_observation::*;
+
 pub mod initialize_with_permission;
 pub use initialize_with_permission::*;
 
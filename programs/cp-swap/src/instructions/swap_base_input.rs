@@ -70,14 +70,24 @@ pub struct Swap<'info> {
     /// The program account for the most recent oracle observation
     #[account(mut, address = pool_state.load()?.observation_key)]
     pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
-    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
-        || block_timestamp < pool_state.open_time
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Swap,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || block_timestamp < pool_state.open_time
     {
         return err!(ErrorCode::NotApproved);
     }
@@ -100,28 +110,44 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         ctx.accounts.output_vault.key(),
         ctx.accounts.input_vault.amount,
         ctx.accounts.output_vault.amount,
+        block_timestamp,
     )?;
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .unwrap();
+    let curve = pool_state.curve(block_timestamp)?;
+    let (total_token_0_amount, total_token_1_amount) = trade_direction.to_token_amounts(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+    );
+    let constant_before = curve
+        .invariant(total_token_0_amount, total_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
 
-    let creator_fee_rate =
-        pool_state.adjust_creator_fee_rate(ctx.accounts.amm_config.creator_fee_rate);
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        pool_state.trade_fee_rate(&ctx.accounts.amm_config),
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        token_0_price_x64,
+    );
+    let fee_rates = pool_state.fee_rates(
+        &ctx.accounts.amm_config,
+        trade_fee_rate,
+        is_creator_fee_on_input,
+    )?;
     let result = CurveCalculator::swap_base_input(
+        curve.as_ref(),
         u128::from(actual_amount_in),
         u128::from(total_input_token_amount),
         u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-        creator_fee_rate,
-        ctx.accounts.amm_config.protocol_fee_rate,
-        ctx.accounts.amm_config.fund_fee_rate,
-        is_creator_fee_on_input,
+        trade_direction,
+        fee_rates,
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
 
-    let constant_after = u128::from(result.new_input_vault_amount)
-        .checked_mul(u128::from(result.new_output_vault_amount))
-        .unwrap();
+    let (new_token_0_amount, new_token_1_amount) = trade_direction.to_token_amounts(
+        result.new_input_vault_amount,
+        result.new_output_vault_amount,
+    );
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
     #[cfg(feature = "enable-log")]
     msg!(
         "input_amount:{}, output_amount:{}, trade_fee:{}, input_transfer_fee:{}, constant_before:{},constant_after:{}, is_creator_fee_on_input:{}, creator_fee:{}",
@@ -176,8 +202,18 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         trade_fee: u64::try_from(result.trade_fee).unwrap(),
         creator_fee: u64::try_from(result.creator_fee).unwrap(),
         creator_fee_on_input: is_creator_fee_on_input,
+        trade_fee_rate,
+        unfilled_amount: 0,
     });
     require_gte!(constant_after, constant_before);
+    pool_state.check_price_deviation(
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        block_timestamp,
+        token_0_price_x64,
+        token_1_price_x64,
+        new_token_0_amount,
+        new_token_1_amount,
+    )?;
 
     transfer_from_user_to_pool_vault(
         ctx.accounts.payer.to_account_info(),
@@ -201,7 +237,7 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
     )?;
 
     // update the previous price to the observation
-    ctx.accounts.observation_state.load_mut()?.update(
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
         oracle::block_timestamp(),
         token_0_price_x64,
         token_1_price_x64,
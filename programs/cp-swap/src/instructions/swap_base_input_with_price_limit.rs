@@ -0,0 +1,225 @@
+use super::swap_base_input::Swap;
+use crate::curve::calculator::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+
+pub fn swap_base_input_with_price_limit(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    limit_price_x32: u128,
+) -> Result<()> {
+    let block_timestamp = oracle::block_timestamp();
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Swap,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let transfer_fee =
+        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    let SwapParams {
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x64,
+        token_1_price_x64,
+        is_creator_fee_on_input,
+    } = pool_state.get_swap_params(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+        block_timestamp,
+    )?;
+    let curve = pool_state.curve(block_timestamp)?;
+    let (total_token_0_amount, total_token_1_amount) = trade_direction.to_token_amounts(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+    );
+    let constant_before = curve
+        .invariant(total_token_0_amount, total_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        pool_state.trade_fee_rate(&ctx.accounts.amm_config),
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        token_0_price_x64,
+    );
+    let fee_rates = pool_state.fee_rates(
+        &ctx.accounts.amm_config,
+        trade_fee_rate,
+        is_creator_fee_on_input,
+    )?;
+    let filled_amount_in = CurveCalculator::max_input_to_price_limit(
+        curve.as_ref(),
+        u128::from(actual_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        fee_rates,
+        limit_price_x32,
+    )
+    .ok_or(ErrorCode::MathOverflow)?;
+    let filled_amount_in = u64::try_from(filled_amount_in).map_err(|_| ErrorCode::MathOverflow)?;
+    // The pool price is already past the limit, nothing is swapped
+    if filled_amount_in == 0 {
+        emit!(SwapEvent {
+            pool_id,
+            input_vault_before: total_input_token_amount,
+            output_vault_before: total_output_token_amount,
+            input_amount: 0,
+            output_amount: 0,
+            input_transfer_fee: 0,
+            output_transfer_fee: 0,
+            base_input: true,
+            input_mint: ctx.accounts.input_token_mint.key(),
+            output_mint: ctx.accounts.output_token_mint.key(),
+            trade_fee: 0,
+            creator_fee: 0,
+            creator_fee_on_input: is_creator_fee_on_input,
+            trade_fee_rate,
+            unfilled_amount: amount_in,
+        });
+        return Ok(());
+    }
+
+    // Only the filled part is taken from the user, including its transfer fee
+    let (input_transfer_amount, input_transfer_fee) = if filled_amount_in == actual_amount_in {
+        (amount_in, transfer_fee)
+    } else {
+        let transfer_fee = get_transfer_inverse_fee(
+            &ctx.accounts.input_token_mint.to_account_info(),
+            filled_amount_in,
+        )?;
+        (
+            filled_amount_in
+                .checked_add(transfer_fee)
+                .ok_or(ErrorCode::MathOverflow)?,
+            transfer_fee,
+        )
+    };
+    let unfilled_amount = amount_in
+        .checked_sub(input_transfer_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let result = CurveCalculator::swap_base_input(
+        curve.as_ref(),
+        u128::from(filled_amount_in),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        fee_rates,
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+    let (new_token_0_amount, new_token_1_amount) = trade_direction.to_token_amounts(
+        result.new_input_vault_amount,
+        result.new_output_vault_amount,
+    );
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "input_amount:{}, output_amount:{}, trade_fee:{}, input_transfer_fee:{}, unfilled_amount:{}, constant_before:{},constant_after:{}, is_creator_fee_on_input:{}, creator_fee:{}",
+        result.input_amount,
+        result.output_amount,
+        result.trade_fee,
+        input_transfer_fee,
+        unfilled_amount,
+        constant_before,
+        constant_after,
+        is_creator_fee_on_input,
+        result.creator_fee,
+    );
+    require_gte!(constant_after, constant_before);
+    pool_state.check_price_deviation(
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        block_timestamp,
+        token_0_price_x64,
+        token_1_price_x64,
+        new_token_0_amount,
+        new_token_1_amount,
+    )?;
+
+    let (output_transfer_amount, output_transfer_fee) = {
+        let amount_out =
+            u64::try_from(result.output_amount).map_err(|_| ErrorCode::MathOverflow)?;
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.output_token_mint.to_account_info(),
+            amount_out,
+        )?;
+        let amount_received = amount_out
+            .checked_sub(transfer_fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require_gt!(amount_received, 0);
+        (amount_out, transfer_fee)
+    };
+
+    pool_state.update_fees(
+        u64::try_from(result.protocol_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        u64::try_from(result.fund_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        u64::try_from(result.creator_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        trade_direction,
+    )?;
+
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount,
+        output_vault_before: total_output_token_amount,
+        input_amount: u64::try_from(result.input_amount).map_err(|_| ErrorCode::MathOverflow)?,
+        output_amount: u64::try_from(result.output_amount).map_err(|_| ErrorCode::MathOverflow)?,
+        input_transfer_fee,
+        output_transfer_fee,
+        base_input: true,
+        input_mint: ctx.accounts.input_token_mint.key(),
+        output_mint: ctx.accounts.output_token_mint.key(),
+        trade_fee: u64::try_from(result.trade_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        creator_fee: u64::try_from(result.creator_fee).map_err(|_| ErrorCode::MathOverflow)?,
+        creator_fee_on_input: is_creator_fee_on_input,
+        trade_fee_rate,
+        unfilled_amount,
+    });
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        ctx.accounts.input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        input_transfer_amount,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        output_transfer_amount,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x64,
+        token_1_price_x64,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,273 @@
+use crate::curve::calculator::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// Number of remaining accounts for every pool of a route, in order:
+/// amm_config, pool_state, input_vault, output_vault, output_token_mint, observation_state
+pub const ROUTE_HOP_ACCOUNTS_LEN: usize = 6;
+
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The user token account for the input token of the first pool
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for the output token of the last pool
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the input token of the first pool
+    #[account(
+        address = input_token_account.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn swap_route<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let hops = ctx.remaining_accounts;
+    if hops.is_empty() || !hops.len().is_multiple_of(ROUTE_HOP_ACCOUNTS_LEN) {
+        return err!(ErrorCode::InvalidRoute);
+    }
+    let hop_count = hops.len() / ROUTE_HOP_ACCOUNTS_LEN;
+    let block_timestamp = oracle::block_timestamp();
+    let token_program_of = |mint: &AccountInfo<'info>| -> AccountInfo<'info> {
+        if mint.owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        }
+    };
+
+    // Every pool pays its output straight into the input vault of the next one,
+    // so the input of a pool is always in its vault before the swap is calculated
+    let input_token_mint = ctx.accounts.input_token_mint.to_account_info();
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        hops[2].clone(),
+        input_token_mint.clone(),
+        token_program_of(&input_token_mint),
+        amount_in,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+    let mut input_transfer_fee = get_transfer_fee(&input_token_mint, amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let mut actual_amount_in = amount_in.saturating_sub(input_transfer_fee);
+    require_gt!(actual_amount_in, 0);
+    let mut input_mint = input_token_mint.key();
+
+    for (index, hop) in hops.chunks(ROUTE_HOP_ACCOUNTS_LEN).enumerate() {
+        let amm_config = Account::<AmmConfig>::try_from(&hop[0])?;
+        let pool_loader = AccountLoader::<PoolState>::try_from(&hop[1])?;
+        let input_vault = InterfaceAccount::<TokenAccount>::try_from(&hop[2])?;
+        let output_vault = InterfaceAccount::<TokenAccount>::try_from(&hop[3])?;
+        let output_token_mint = InterfaceAccount::<Mint>::try_from(&hop[4])?;
+        let observation_loader = AccountLoader::<ObservationState>::try_from(&hop[5])?;
+
+        let pool_id = pool_loader.key();
+        let pool_state = &mut pool_loader.load_mut()?;
+        require_keys_eq!(
+            amm_config.key(),
+            pool_state.amm_config,
+            ErrorCode::InvalidRoute
+        );
+        require_keys_eq!(
+            observation_loader.key(),
+            pool_state.observation_key,
+            ErrorCode::InvalidRoute
+        );
+        require_keys_eq!(input_vault.mint, input_mint, ErrorCode::InvalidRoute);
+        require_keys_eq!(
+            output_token_mint.key(),
+            output_vault.mint,
+            ErrorCode::InvalidRoute
+        );
+        if !pool_state.get_status_by_bit_with_configs(
+            PoolStatusBitIndex::Swap,
+            &amm_config,
+            &ctx.accounts.global_config,
+        ) || block_timestamp < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        let SwapParams {
+            trade_direction,
+            total_input_token_amount,
+            total_output_token_amount,
+            token_0_price_x64,
+            token_1_price_x64,
+            is_creator_fee_on_input,
+        } = pool_state.get_swap_params(
+            input_vault.key(),
+            output_vault.key(),
+            input_vault
+                .amount
+                .checked_sub(actual_amount_in)
+                .ok_or(ErrorCode::InsufficientVault)?,
+            output_vault.amount,
+            block_timestamp,
+        )?;
+        let curve = pool_state.curve(block_timestamp)?;
+        let (total_token_0_amount, total_token_1_amount) = trade_direction.to_token_amounts(
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+        );
+        let constant_before = curve
+            .invariant(total_token_0_amount, total_token_1_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let trade_fee_rate = amm_config.effective_trade_fee_rate(
+            pool_state.trade_fee_rate(&amm_config),
+            &oracle::load_observations(&observation_loader)?,
+            token_0_price_x64,
+        );
+        let fee_rates =
+            pool_state.fee_rates(&amm_config, trade_fee_rate, is_creator_fee_on_input)?;
+        let result = CurveCalculator::swap_base_input(
+            curve.as_ref(),
+            u128::from(actual_amount_in),
+            u128::from(total_input_token_amount),
+            u128::from(total_output_token_amount),
+            trade_direction,
+            fee_rates,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+
+        let (new_token_0_amount, new_token_1_amount) = trade_direction.to_token_amounts(
+            result.new_input_vault_amount,
+            result.new_output_vault_amount,
+        );
+        let constant_after = curve
+            .invariant(new_token_0_amount, new_token_1_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "hop:{}, input_amount:{}, output_amount:{}, trade_fee:{}, input_transfer_fee:{}, constant_before:{},constant_after:{}, is_creator_fee_on_input:{}, creator_fee:{}",
+            index,
+            result.input_amount,
+            result.output_amount,
+            result.trade_fee,
+            input_transfer_fee,
+            constant_before,
+            constant_after,
+            is_creator_fee_on_input,
+            result.creator_fee,
+        );
+        require_eq!(
+            u64::try_from(result.input_amount).unwrap(),
+            actual_amount_in
+        );
+        require_gte!(constant_after, constant_before);
+        pool_state.check_price_deviation(
+            &oracle::load_observations(&observation_loader)?,
+            block_timestamp,
+            token_0_price_x64,
+            token_1_price_x64,
+            new_token_0_amount,
+            new_token_1_amount,
+        )?;
+
+        let amount_out = u64::try_from(result.output_amount).unwrap();
+        let output_transfer_fee =
+            get_transfer_fee(&output_token_mint.to_account_info(), amount_out)?;
+        let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+        require_gt!(amount_received, 0);
+        let is_last_hop = index + 1 == hop_count;
+        if is_last_hop {
+            require_gte!(
+                amount_received,
+                minimum_amount_out,
+                ErrorCode::ExceededSlippage
+            );
+        }
+
+        pool_state.update_fees(
+            u64::try_from(result.protocol_fee).unwrap(),
+            u64::try_from(result.fund_fee).unwrap(),
+            u64::try_from(result.creator_fee).unwrap(),
+            trade_direction,
+        )?;
+
+        emit!(SwapEvent {
+            pool_id,
+            input_vault_before: total_input_token_amount,
+            output_vault_before: total_output_token_amount,
+            input_amount: u64::try_from(result.input_amount).unwrap(),
+            output_amount: amount_out,
+            input_transfer_fee,
+            output_transfer_fee,
+            base_input: true,
+            input_mint,
+            output_mint: output_token_mint.key(),
+            trade_fee: u64::try_from(result.trade_fee).unwrap(),
+            creator_fee: u64::try_from(result.creator_fee).unwrap(),
+            creator_fee_on_input: is_creator_fee_on_input,
+            trade_fee_rate,
+            unfilled_amount: 0,
+        });
+
+        let destination = if is_last_hop {
+            ctx.accounts.output_token_account.to_account_info()
+        } else {
+            hops[(index + 1) * ROUTE_HOP_ACCOUNTS_LEN + 2].clone()
+        };
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            output_vault.to_account_info(),
+            destination,
+            output_token_mint.to_account_info(),
+            token_program_of(&output_token_mint.to_account_info()),
+            amount_out,
+            output_token_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        )?;
+
+        // update the previous price to the observation
+        oracle::load_observations_mut(&observation_loader)?.update(
+            oracle::block_timestamp(),
+            token_0_price_x64,
+            token_1_price_x64,
+        )?;
+        pool_state.recent_epoch = Clock::get()?.epoch;
+
+        input_mint = output_token_mint.key();
+        input_transfer_fee = output_transfer_fee;
+        actual_amount_in = amount_received;
+    }
+
+    Ok(())
+}
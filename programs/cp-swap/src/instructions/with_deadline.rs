@@ -0,0 +1,66 @@
+use super::{deposit::Deposit, swap_base_input::Swap, withdraw::Withdraw};
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Fails once the block timestamp is past `deadline`, a unix timestamp
+pub fn check_deadline(deadline: u64) -> Result<()> {
+    require_gte!(
+        deadline,
+        oracle::block_timestamp(),
+        ErrorCode::DeadlineExceeded
+    );
+    Ok(())
+}
+
+pub fn swap_base_input_with_deadline(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    deadline: u64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    super::swap_base_input(ctx, amount_in, minimum_amount_out)
+}
+
+pub fn swap_base_output_with_deadline(
+    ctx: Context<Swap>,
+    max_amount_in: u64,
+    amount_out_received: u64,
+    deadline: u64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    super::swap_base_output(ctx, max_amount_in, amount_out_received)
+}
+
+pub fn deposit_with_deadline(
+    ctx: Context<Deposit>,
+    lp_token_amount: u64,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    deadline: u64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    super::deposit(
+        ctx,
+        lp_token_amount,
+        maximum_token_0_amount,
+        maximum_token_1_amount,
+    )
+}
+
+pub fn withdraw_with_deadline(
+    ctx: Context<Withdraw>,
+    lp_token_amount: u64,
+    minimum_token_0_amount: u64,
+    minimum_token_1_amount: u64,
+    deadline: u64,
+) -> Result<()> {
+    check_deadline(deadline)?;
+    super::withdraw(
+        ctx,
+        lp_token_amount,
+        minimum_token_0_amount,
+        minimum_token_1_amount,
+    )
+}
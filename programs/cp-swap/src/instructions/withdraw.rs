@@ -94,6 +94,21 @@ pub struct Withdraw<'info> {
         address = spl_memo::id()
     )]
     pub memo_program: UncheckedAccount<'info>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The amm config holding the status of its pools
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
 }
 
 pub fn withdraw(
@@ -106,14 +121,25 @@ pub fn withdraw(
     require_gte!(ctx.accounts.owner_lp_token.amount, lp_token_amount);
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
-    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Withdraw,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) {
         return err!(ErrorCode::NotApproved);
     }
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     )?;
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+        oracle::block_timestamp(),
+    )?;
+    let curve = pool_state.curve(oracle::block_timestamp())?;
     let results = CurveCalculator::lp_tokens_to_trading_tokens(
+        curve.as_ref(),
         u128::from(lp_token_amount),
         u128::from(pool_state.lp_supply),
         u128::from(total_token_0_amount),
@@ -121,7 +147,14 @@ pub fn withdraw(
         RoundDirection::Floor,
     )
     .ok_or(ErrorCode::ZeroTradingTokens)?;
-    if results.token_0_amount == 0 || results.token_1_amount == 0 {
+    // An offset pool may hold no real token_1 at all, its lp only withdraw token_0 then
+    let (_, lp_token_1_amount) = curve
+        .lp_vault_amounts(
+            u128::from(total_token_0_amount),
+            u128::from(total_token_1_amount),
+        )
+        .ok_or(ErrorCode::InsufficientVault)?;
+    if results.token_0_amount == 0 || (results.token_1_amount == 0 && lp_token_1_amount != 0) {
         return err!(ErrorCode::ZeroTradingTokens);
     }
     let token_0_amount = u64::try_from(results.token_0_amount).unwrap();
@@ -214,6 +247,12 @@ pub fn withdraw(
         ctx.accounts.vault_1_mint.decimals,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+    )?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
@@ -0,0 +1,161 @@
+use super::withdraw::Withdraw;
+use crate::curve::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+
+pub fn withdraw_by_amounts(
+    ctx: Context<Withdraw>,
+    token_0_amount: u64,
+    token_1_amount: u64,
+    max_lp_burn: u64,
+) -> Result<()> {
+    if token_0_amount == 0 && token_1_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Withdraw,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) {
+        return err!(ErrorCode::NotApproved);
+    }
+    let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+    )?;
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32(
+        ctx.accounts.token_0_vault.amount,
+        ctx.accounts.token_1_vault.amount,
+        oracle::block_timestamp(),
+    )?;
+    let curve = pool_state.curve(oracle::block_timestamp())?;
+
+    // The amounts are what the owner receives at least, the vaults also pay the transfer fees
+    let minimum_transfer_token_0_amount = token_0_amount
+        .checked_add(get_transfer_inverse_fee(
+            &ctx.accounts.vault_0_mint.to_account_info(),
+            token_0_amount,
+        )?)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let minimum_transfer_token_1_amount = token_1_amount
+        .checked_add(get_transfer_inverse_fee(
+            &ctx.accounts.vault_1_mint.to_account_info(),
+            token_1_amount,
+        )?)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // The burnt share is paid out at the pool ratio, so a one sided request
+    // also receives the other token instead of leaving its value in the pool
+    let (lp_token_amount, results) = CurveCalculator::lp_tokens_to_withdraw(
+        curve.as_ref(),
+        u128::from(minimum_transfer_token_0_amount),
+        u128::from(minimum_transfer_token_1_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_token_0_amount),
+        u128::from(total_token_1_amount),
+    )
+    .ok_or(ErrorCode::InsufficientVault)?;
+    let lp_token_amount = u64::try_from(lp_token_amount).map_err(|_| ErrorCode::MathOverflow)?;
+    require_gt!(lp_token_amount, 0);
+    require_gte!(ctx.accounts.owner_lp_token.amount, lp_token_amount);
+    if lp_token_amount > max_lp_burn {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    let transfer_token_0_amount =
+        u64::try_from(results.token_0_amount).map_err(|_| ErrorCode::MathOverflow)?;
+    let token_0_transfer_fee = get_transfer_fee(
+        &ctx.accounts.vault_0_mint.to_account_info(),
+        transfer_token_0_amount,
+    )?;
+    let received_token_0_amount = transfer_token_0_amount
+        .checked_sub(token_0_transfer_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let transfer_token_1_amount =
+        u64::try_from(results.token_1_amount).map_err(|_| ErrorCode::MathOverflow)?;
+    let token_1_transfer_fee = get_transfer_fee(
+        &ctx.accounts.vault_1_mint.to_account_info(),
+        transfer_token_1_amount,
+    )?;
+    let received_token_1_amount = transfer_token_1_amount
+        .checked_sub(token_1_transfer_fee)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if received_token_0_amount < token_0_amount || received_token_1_amount < token_1_amount {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "lp_token_amount:{}, transfer_token_0_amount:{},token_0_transfer_fee:{},
+            transfer_token_1_amount:{},token_1_transfer_fee:{}",
+        lp_token_amount,
+        transfer_token_0_amount,
+        token_0_transfer_fee,
+        transfer_token_1_amount,
+        token_1_transfer_fee
+    );
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: total_token_0_amount,
+        token_1_vault_before: total_token_1_amount,
+        token_0_amount: received_token_0_amount,
+        token_1_amount: received_token_1_amount,
+        token_0_transfer_fee,
+        token_1_transfer_fee,
+        change_type: 1
+    });
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        transfer_token_0_amount,
+        ctx.accounts.vault_0_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        transfer_token_1_amount,
+        ctx.accounts.vault_1_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use crate::curve::CurveCalculator;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(lp_token_amount: u64, output_mint: Pubkey)]
+pub struct WithdrawSingleToken<'info> {
+    /// Pays to mint the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read trade fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Pool state account
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Owner lp token account
+    #[account(
+        mut,
+        token::authority = owner
+    )]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token account for receive the output token
+    #[account(
+        mut,
+        token::mint = output_mint,
+    )]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for the token swapped back into the pool
+    #[account(
+        constraint = input_vault.key() == pool_state.load()?.token_0_vault || input_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for the output token
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.load()?.token_0_vault || output_vault.key() == pool_state.load()?.token_1_vault,
+        constraint = output_vault.mint == output_mint
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program for the output token transfers
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of the output token
+    #[account(
+        address = output_mint
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool lp token mint
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint)
+    ]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The global config holding the status of every pool
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED.as_bytes()],
+        bump = global_config.bump,
+    )]
+    pub global_config: Box<Account<'info, GlobalConfig>>,
+}
+
+pub fn withdraw_single_token(
+    ctx: Context<WithdrawSingleToken>,
+    lp_token_amount: u64,
+    _output_mint: Pubkey,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    require_gt!(lp_token_amount, 0);
+    require_gte!(ctx.accounts.owner_lp_token.amount, lp_token_amount);
+    let block_timestamp = oracle::block_timestamp();
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // Part of the withdrawal is swapped, so the pool has to be open for both
+    if !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Withdraw,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || !pool_state.get_status_by_bit_with_configs(
+        PoolStatusBitIndex::Swap,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.global_config,
+    ) || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let SwapParams {
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+        token_0_price_x64,
+        token_1_price_x64,
+        is_creator_fee_on_input,
+    } = pool_state.get_swap_params(
+        ctx.accounts.input_vault.key(),
+        ctx.accounts.output_vault.key(),
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+        block_timestamp,
+    )?;
+    let curve = pool_state.curve(block_timestamp)?;
+
+    let trade_fee_rate = ctx.accounts.amm_config.effective_trade_fee_rate(
+        pool_state.trade_fee_rate(&ctx.accounts.amm_config),
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        token_0_price_x64,
+    );
+    let fee_rates = pool_state.fee_rates(
+        &ctx.accounts.amm_config,
+        trade_fee_rate,
+        is_creator_fee_on_input,
+    )?;
+    let result = CurveCalculator::withdraw_single_token(
+        curve.as_ref(),
+        u128::from(lp_token_amount),
+        u128::from(pool_state.lp_supply),
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        trade_direction,
+        fee_rates,
+    )
+    .ok_or(ErrorCode::ZeroTradingTokens)?;
+    let swap = &result.swap;
+
+    // The invariant of the pool left after the withdrawal must not decrease from the swap
+    let (token_0_amount_before, token_1_amount_before) = trade_direction.to_token_amounts(
+        u128::from(total_input_token_amount) - result.withdraw_input_amount,
+        u128::from(total_output_token_amount) - result.withdraw_output_amount,
+    );
+    let constant_before = curve
+        .invariant(token_0_amount_before, token_1_amount_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let (new_token_0_amount, new_token_1_amount) =
+        trade_direction.to_token_amounts(swap.new_input_vault_amount, swap.new_output_vault_amount);
+    let constant_after = curve
+        .invariant(new_token_0_amount, new_token_1_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let output_amount = u64::try_from(result.output_amount).unwrap();
+    if output_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    // Only the output token leaves the pool, so only its transfer fee applies
+    let output_transfer_fee = get_transfer_fee(
+        &ctx.accounts.output_token_mint.to_account_info(),
+        output_amount,
+    )?;
+    let amount_received = output_amount.checked_sub(output_transfer_fee).unwrap();
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "withdraw_input_amount:{}, withdraw_output_amount:{}, swap output_amount:{}, trade_fee:{}, output_transfer_fee:{}, constant_before:{}, constant_after:{}",
+        result.withdraw_input_amount,
+        result.withdraw_output_amount,
+        swap.output_amount,
+        swap.trade_fee,
+        output_transfer_fee,
+        constant_before,
+        constant_after
+    );
+    require_gte!(constant_after, constant_before);
+    pool_state.check_price_deviation(
+        &oracle::load_observations(&ctx.accounts.observation_state)?,
+        block_timestamp,
+        token_0_price_x64,
+        token_1_price_x64,
+        new_token_0_amount,
+        new_token_1_amount,
+    )?;
+    require_gt!(amount_received, 0);
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    let (token_0_amount, token_1_amount) = trade_direction
+        .to_token_amounts(result.withdraw_input_amount, result.withdraw_output_amount);
+    let (token_0_transfer_fee, token_1_transfer_fee) =
+        trade_direction.to_token_amounts(0, u128::from(output_transfer_fee));
+    let (token_0_vault_before, token_1_vault_before) = trade_direction.to_token_amounts(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+    );
+    emit!(LpChangeEvent {
+        pool_id,
+        lp_amount_before: pool_state.lp_supply,
+        token_0_vault_before: u64::try_from(token_0_vault_before).unwrap(),
+        token_1_vault_before: u64::try_from(token_1_vault_before).unwrap(),
+        token_0_amount: u64::try_from(token_0_amount).unwrap(),
+        token_1_amount: u64::try_from(token_1_amount).unwrap(),
+        token_0_transfer_fee: u64::try_from(token_0_transfer_fee).unwrap(),
+        token_1_transfer_fee: u64::try_from(token_1_transfer_fee).unwrap(),
+        change_type: 1
+    });
+
+    // The withdrawn input tokens never leave the pool, they are swapped back right away
+    emit!(SwapEvent {
+        pool_id,
+        input_vault_before: total_input_token_amount
+            - u64::try_from(result.withdraw_input_amount).unwrap(),
+        output_vault_before: total_output_token_amount
+            - u64::try_from(result.withdraw_output_amount).unwrap(),
+        input_amount: u64::try_from(swap.input_amount).unwrap(),
+        output_amount: u64::try_from(swap.output_amount).unwrap(),
+        input_transfer_fee: 0,
+        output_transfer_fee: 0,
+        base_input: true,
+        input_mint: ctx.accounts.input_vault.mint,
+        output_mint: ctx.accounts.output_vault.mint,
+        trade_fee: u64::try_from(swap.trade_fee).unwrap(),
+        creator_fee: u64::try_from(swap.creator_fee).unwrap(),
+        creator_fee_on_input: is_creator_fee_on_input,
+        trade_fee_rate,
+        unfilled_amount: 0,
+    });
+
+    pool_state.update_fees(
+        u64::try_from(swap.protocol_fee).unwrap(),
+        u64::try_from(swap.fund_fee).unwrap(),
+        u64::try_from(swap.creator_fee).unwrap(),
+        trade_direction,
+    )?;
+
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        output_amount,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    // update the previous price to the observation
+    oracle::load_observations_mut(&ctx.accounts.observation_state)?.update(
+        oracle::block_timestamp(),
+        token_0_price_x64,
+        token_1_price_x64,
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -4,9 +4,10 @@ pub mod instructions;
 pub mod states;
 pub mod utils;
 use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::curve::CurveParams;
 use anchor_lang::prelude::*;
 use instructions::*;
-pub use states::CreatorFeeOn;
+pub use states::{AmmConfigUpdate, CreatorFeeOn, GlobalRole, PoolFeeRateUpdate};
 
 #[cfg(not(feature = "no-entrypoint"))]
 solana_security_txt::security_txt! {
@@ -24,6 +25,7 @@ declare_id!("DRaycpLY18LhpbydsBWbVJtxpNv9oXPgjRSfpF2bWpYb");
 #[cfg(not(feature = "devnet"))]
 declare_id!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
 
+/// The deploy time admin, which creates the global config holding the current admin
 pub mod admin {
     use super::{pubkey, Pubkey};
     #[cfg(feature = "devnet")]
@@ -46,6 +48,56 @@ pub const AUTH_SEED: &str = "vault_and_lp_mint_auth_seed";
 pub mod raydium_cp_swap {
     use super::*;
 
+    /// Create the global config holding the admin of the program, signed by the deploy
+    /// time admin which becomes its first admin
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    ///
+    pub fn create_global_config(ctx: Context<CreateGlobalConfig>) -> Result<()> {
+        instructions::create_global_config(ctx)
+    }
+
+    /// Propose a new admin, which takes over once it calls `accept_admin`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    /// * `new_admin` - The proposed admin, the default pubkey cancels a pending transfer.
+    ///
+    pub fn transfer_admin(ctx: Context<TransferAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::transfer_admin(ctx, new_admin)
+    }
+
+    /// Accept the admin role proposed by `transfer_admin`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    ///
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    /// Grant a role allowed a part of the admin instructions: the guardian can only disable
+    /// pool status bits, the fee manager can update the fee params of amm configs and the
+    /// listing manager can create and close support mint associated accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    /// * `role` - The role to grant.
+    /// * `authority` - The new holder of the role, the default pubkey revokes it.
+    ///
+    pub fn update_role(
+        ctx: Context<UpdateRole>,
+        role: GlobalRole,
+        authority: Pubkey,
+    ) -> Result<()> {
+        instructions::update_role(ctx, role, authority)
+    }
+
     // The configuration of AMM protocol, include trade fee and protocol fee
     /// # Arguments
     ///
@@ -79,24 +131,59 @@ pub mod raydium_cp_swap {
         )
     }
 
-    /// Updates the owner of the amm config
-    /// Must be called by the current owner or admin
+    /// Instantly apply an update which can only make the amm config safer, which is
+    /// disabling pool creation. Every other update goes through `queue_amm_config_update`.
+    /// Must be called by the admin
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `trade_fee_rate`- The new trade fee rate of amm config, be set when `param` is 0
-    /// * `protocol_fee_rate`- The new protocol fee rate of amm config, be set when `param` is 1
-    /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
-    /// * `new_owner`- The config's new owner, be set when `param` is 3
-    /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The value can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
+    /// * `update`- `DisableCreatePool(true)`
     ///
-    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
-        instructions::update_amm_config(ctx, param, value)
+    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, update: AmmConfigUpdate) -> Result<()> {
+        instructions::update_amm_config(ctx, update)
     }
 
-    /// Update pool status for given value
+    /// Queue an update of an amm config, which anyone can execute once `activation_timestamp`
+    /// is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update`- The parameter to update with its new value, a `MaxTradeFeeRate` of zero
+    ///   turns the dynamic trade fee off
+    /// * `activation_timestamp`- The timestamp from which the update can be executed, at least
+    ///   `AMM_CONFIG_UPDATE_DELAY` from now
+    ///
+    pub fn queue_amm_config_update(
+        ctx: Context<UpdateAmmConfig>,
+        update: AmmConfigUpdate,
+        activation_timestamp: u64,
+    ) -> Result<()> {
+        instructions::queue_amm_config_update(ctx, update, activation_timestamp)
+    }
+
+    /// Execute the queued update of an amm config after its activation timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn execute_amm_config_update(ctx: Context<ExecuteAmmConfigUpdate>) -> Result<()> {
+        instructions::execute_amm_config_update(ctx)
+    }
+
+    /// Cancel the queued update of an amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_amm_config_update(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+        instructions::cancel_amm_config_update(ctx)
+    }
+
+    /// Update pool status for given value, the guardian can only disable
     ///
     /// # Arguments
     ///
@@ -107,6 +194,114 @@ pub mod raydium_cp_swap {
         instructions::update_pool_status(ctx, status)
     }
 
+    /// Update the status of every pool of an amm config, checked alongside the pool status.
+    /// The guardian can only disable.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `status` - The value of status, with the same bits as the pool status
+    ///
+    pub fn update_amm_config_status(ctx: Context<UpdateAmmConfigStatus>, status: u8) -> Result<()> {
+        instructions::update_amm_config_status(ctx, status)
+    }
+
+    /// Update the status of every pool, a kill switch independent of the amm configs.
+    /// The guardian can only disable.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `status` - The value of status, with the same bits as the pool status
+    ///
+    pub fn update_global_status(ctx: Context<UpdateGlobalStatus>, status: u8) -> Result<()> {
+        instructions::update_global_status(ctx, status)
+    }
+
+    /// Ramp the amplification coefficient of a stable swap pool linearly over time
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `target_amp` - The amplification coefficient to reach
+    /// * `stop_ramp_ts` - The timestamp the target is reached, at least one day from now
+    ///
+    pub fn ramp_amp(ctx: Context<RampAmp>, target_amp: u64, stop_ramp_ts: u64) -> Result<()> {
+        instructions::ramp_amp(ctx, target_amp, stop_ramp_ts)
+    }
+
+    /// Stop the amplification coefficient ramp of a stable swap pool at its current value
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn stop_ramp_amp(ctx: Context<RampAmp>) -> Result<()> {
+        instructions::stop_ramp_amp(ctx)
+    }
+
+    /// Update the minimum time between two oracle observations of a pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update_duration` - The minimum time between two observations in seconds
+    ///
+    pub fn update_observation_duration(
+        ctx: Context<UpdateObservationDuration>,
+        update_duration: u64,
+    ) -> Result<()> {
+        instructions::update_observation_duration(ctx, update_duration)
+    }
+
+    /// Limit how far a swap may move the token_0 price of a pool away from its time weighted
+    /// average price, swaps moving it further are rejected. This includes the swaps made by
+    /// single token deposits and withdrawals.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_price_deviation_bps` - The maximum deviation in bps, at most 10000, 0 disables the check
+    /// * `price_deviation_window` - The window of the time weighted average price in seconds,
+    ///   at most the history the oracle keeps, `(observation_num - 1) * update_duration`
+    ///
+    pub fn update_price_deviation_limit(
+        ctx: Context<UpdatePriceDeviationLimit>,
+        max_price_deviation_bps: u64,
+        price_deviation_window: u64,
+    ) -> Result<()> {
+        instructions::update_price_deviation_limit(
+            ctx,
+            max_price_deviation_bps,
+            price_deviation_window,
+        )
+    }
+
+    /// Override a fee rate of the amm config for a single pool, or fall back to the config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update`- The fee rate to override with its value for the pool, None clears the override
+    ///
+    pub fn update_pool_fee_rate(
+        ctx: Context<UpdatePoolFeeRate>,
+        update: PoolFeeRateUpdate,
+    ) -> Result<()> {
+        instructions::update_pool_fee_rate(ctx, update)
+    }
+
+    /// Move a pool to another amm config, the fee rates of the pool under the new config
+    /// must be valid
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_pool_config(ctx: Context<MigratePoolConfig>) -> Result<()> {
+        instructions::migrate_pool_config(ctx)
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -184,7 +379,39 @@ pub mod raydium_cp_swap {
         init_amount_1: u64,
         open_time: u64,
     ) -> Result<()> {
-        instructions::initialize(ctx, init_amount_0, init_amount_1, open_time)
+        instructions::initialize(
+            ctx,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            CurveParams::ConstantProduct,
+        )
+    }
+
+    /// Creates a pool for the given token pair and the initial price, priced by the given curve
+    ///
+    /// The weighted curve computes its invariant `x^w0 * y^w1` exactly in 512 bits, so the
+    /// weights reduced by their gcd must sum to at most `MAX_WEIGHT_SUM` (7). 50/50, 60/40,
+    /// 75/25 and 80/20 pools can be created, but 70/30 (7/3) and 90/10 (9/1) pools are
+    /// rejected with `WeightSumTooHigh`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `init_amount_0` - the initial amount_0 to deposit
+    /// * `init_amount_1` - the initial amount_1 to deposit
+    /// * `open_time` - the timestamp allowed for swap
+    /// * `curve_params` - the curve invariant of the pool and its parameters, see
+    ///   `CurveParams` for the bounds of each curve
+    ///
+    pub fn initialize_with_curve(
+        ctx: Context<Initialize>,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        curve_params: CurveParams,
+    ) -> Result<()> {
+        instructions::initialize(ctx, init_amount_0, init_amount_1, open_time, curve_params)
     }
 
     /// Create a pool with permission
@@ -236,6 +463,71 @@ pub mod raydium_cp_swap {
         )
     }
 
+    /// Deposit lp token to the pool, failing once the deadline has passed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Increased number of LPs
+    /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, prevents excessive slippage
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
+    /// * `deadline` - The unix timestamp after which the transaction fails
+    ///
+    pub fn deposit_with_deadline(
+        ctx: Context<Deposit>,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::deposit_with_deadline(
+            ctx,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            deadline,
+        )
+    }
+
+    /// Deposit token0 and token1 by amounts, minting as many LPs as the amounts allow
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `maximum_token_0_amount` - Maximum token 0 amount to deposit, including transfer fees
+    /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, including transfer fees
+    /// * `minimum_lp_out` - Minimum number of LPs to receive, prevents excessive slippage
+    ///
+    pub fn deposit_by_amounts(
+        ctx: Context<Deposit>,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_out: u64,
+    ) -> Result<()> {
+        instructions::deposit_by_amounts(
+            ctx,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_out,
+        )
+    }
+
+    /// Deposit a single token to the pool, part of it is swapped into the other token
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` - Amount of the input token to deposit
+    /// * `minimum_lp_out` - Minimum number of LPs to receive, prevents excessive slippage
+    ///
+    pub fn deposit_single_token(
+        ctx: Context<DepositSingleToken>,
+        amount_in: u64,
+        minimum_lp_out: u64,
+    ) -> Result<()> {
+        instructions::deposit_single_token(ctx, amount_in, minimum_lp_out)
+    }
+
     /// Withdraw lp for token0 and token1
     ///
     /// # Arguments
@@ -259,6 +551,72 @@ pub mod raydium_cp_swap {
         )
     }
 
+    /// Withdraw lp for token0 and token1, failing once the deadline has passed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `minimum_token_0_amount` -  Minimum amount of token 0 to receive, prevents excessive slippage
+    /// * `minimum_token_1_amount` -  Minimum amount of token 1 to receive, prevents excessive slippage
+    /// * `deadline` - The unix timestamp after which the transaction fails
+    ///
+    pub fn withdraw_with_deadline(
+        ctx: Context<Withdraw>,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::withdraw_with_deadline(
+            ctx,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+            deadline,
+        )
+    }
+
+    /// Withdraw the given amounts of token0 and token1, burning the fewest lp they are worth.
+    ///
+    /// The burnt lp is paid out pro rata at the pool ratio like `withdraw`, so the amounts are
+    /// what the owner receives at least: the token asked for less, or not at all, also pays
+    /// out its share of the burnt lp instead of leaving its value in the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `token_0_amount` - Amount of token 0 to receive at least
+    /// * `token_1_amount` - Amount of token 1 to receive at least
+    /// * `max_lp_burn` - Maximum number of LPs to burn, prevents excessive slippage
+    ///
+    pub fn withdraw_by_amounts(
+        ctx: Context<Withdraw>,
+        token_0_amount: u64,
+        token_1_amount: u64,
+        max_lp_burn: u64,
+    ) -> Result<()> {
+        instructions::withdraw_by_amounts(ctx, token_0_amount, token_1_amount, max_lp_burn)
+    }
+
+    /// Withdraw lp for a single token, the other token withdrawn is swapped back into the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn
+    /// * `output_mint` - The mint of the token to receive
+    /// * `minimum_amount_out` - Minimum amount of the output token to receive, prevents excessive slippage
+    ///
+    pub fn withdraw_single_token(
+        ctx: Context<WithdrawSingleToken>,
+        lp_token_amount: u64,
+        output_mint: Pubkey,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::withdraw_single_token(ctx, lp_token_amount, output_mint, minimum_amount_out)
+    }
+
     /// Swap the tokens in the pool base input amount
     ///
     /// # Arguments
@@ -275,6 +633,24 @@ pub mod raydium_cp_swap {
         instructions::swap_base_input(ctx, amount_in, minimum_amount_out)
     }
 
+    /// Swap the tokens in the pool base input amount, failing once the deadline has passed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `deadline` - The unix timestamp after which the transaction fails
+    ///
+    pub fn swap_base_input_with_deadline(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::swap_base_input_with_deadline(ctx, amount_in, minimum_amount_out, deadline)
+    }
+
     /// Swap the tokens in the pool base output amount
     ///
     /// # Arguments
@@ -287,6 +663,130 @@ pub mod raydium_cp_swap {
         instructions::swap_base_output(ctx, max_amount_in, amount_out)
     }
 
+    /// Swap the tokens in the pool base output amount, failing once the deadline has passed
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_amount_in` -  input amount prevents excessive slippage
+    /// * `amount_out` -  amount of output token
+    /// * `deadline` - The unix timestamp after which the transaction fails
+    ///
+    pub fn swap_base_output_with_deadline(
+        ctx: Context<Swap>,
+        max_amount_in: u64,
+        amount_out: u64,
+        deadline: u64,
+    ) -> Result<()> {
+        instructions::swap_base_output_with_deadline(ctx, max_amount_in, amount_out, deadline)
+    }
+
+    /// Swap the tokens in the pool base input amount, only as much of the input is swapped as
+    /// keeps the price of the input token above the limit, the rest is left with the user.
+    /// Nothing is swapped when the pool price is already past the limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  Maximum input amount to transfer
+    /// * `limit_price_x32` -  Minimum marginal price of the input token in the output token after the swap, Q32.32
+    ///
+    pub fn swap_base_input_with_price_limit(
+        ctx: Context<Swap>,
+        amount_in: u64,
+        limit_price_x32: u128,
+    ) -> Result<()> {
+        instructions::swap_base_input_with_price_limit(ctx, amount_in, limit_price_x32)
+    }
+
+    /// Swap the tokens through several pools base input amount, the pools are passed
+    /// as remaining accounts, `ROUTE_HOP_ACCOUNTS_LEN` accounts for each pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount to transfer into the first pool
+    /// * `minimum_amount_out` -  Minimum amount of output token from the last pool, prevents excessive slippage
+    ///
+    pub fn swap_route<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_route(ctx, amount_in, minimum_amount_out)
+    }
+
+    /// Borrow tokens from the pool vaults, the borrower program is called back with the
+    /// remaining accounts and must repay the vaults plus the flash fee before returning
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_0` - Amount of token 0 to borrow
+    /// * `amount_1` - Amount of token 1 to borrow
+    ///
+    pub fn flash_loan<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, FlashLoan<'info>>,
+        amount_0: u64,
+        amount_1: u64,
+    ) -> Result<()> {
+        instructions::flash_loan(ctx, amount_0, amount_1)
+    }
+
+    /// Swap the tokens in the pool base output amount, the output is sent first and the
+    /// borrower program is called back with the remaining accounts to pay the input
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `max_amount_in` -  input amount prevents excessive slippage
+    /// * `amount_out` -  amount of output token
+    ///
+    pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+        max_amount_in: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        instructions::flash_swap(ctx, max_amount_in, amount_out)
+    }
+
+    /// Returns the time weighted average prices of the pool through the return data,
+    /// serialized as `TwapPrice`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `window` - The length of the averaging period in seconds
+    ///
+    pub fn consult_twap(ctx: Context<ConsultTwap>, window: u64) -> Result<()> {
+        instructions::consult_twap(ctx, window)
+    }
+
+    /// Grow the oracle observation account of a pool to hold more observations, so the
+    /// time weighted average prices can be consulted over a longer window
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `observation_num` - The new number of observations, at most 256 more than the current one
+    ///
+    pub fn increase_observation_num(
+        ctx: Context<IncreaseObservationNum>,
+        observation_num: u16,
+    ) -> Result<()> {
+        instructions::increase_observation_num(ctx, observation_num)
+    }
+
+    /// Checkpoint the current price of the pool into its oracle observations without trading
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn update_observation(ctx: Context<UpdateObservation>) -> Result<()> {
+        instructions::update_observation(ctx)
+    }
+
     /// Create support token22 mint account which can create pool and send rewards while ignoring unsupported extensions.
     pub fn create_support_mint_associated(ctx: Context<CreateSupportMintAssociated>) -> Result<()> {
         instructions::create_support_mint_associated(ctx)
@@ -1,6 +1,127 @@
+use crate::error::ErrorCode;
+use crate::states::{Observation, ObservationRing, ObservationState, PoolStatusBitIndex};
 use anchor_lang::prelude::*;
+use std::ops::{BitAnd, Deref};
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+/// The minimum time between queueing an amm config update and executing it, in seconds
+pub const AMM_CONFIG_UPDATE_DELAY: u64 = 24 * 3600;
+
+/// A parameter of an amm config with its new value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmConfigUpdate {
+    /// The trade fee rate, denominated in hundredths of a bip (10^-6)
+    TradeFeeRate(u64),
+    /// The protocol fee rate, denominated in hundredths of a bip (10^-6)
+    ProtocolFeeRate(u64),
+    /// The fund fee rate, denominated in hundredths of a bip (10^-6)
+    FundFeeRate(u64),
+    /// The owner of the protocol fee
+    ProtocolOwner(Pubkey),
+    /// The owner of the fund fee
+    FundOwner(Pubkey),
+    /// The fee for creating a new pool
+    CreatePoolFee(u64),
+    /// Whether new pools can be created
+    DisableCreatePool(bool),
+    /// The pool creator fee rate, denominated in hundredths of a bip (10^-6)
+    CreatorFeeRate(u64),
+    /// The lower bound of the dynamic trade fee
+    MinTradeFeeRate(u64),
+    /// The upper bound of the dynamic trade fee, zero turns the dynamic trade fee off
+    MaxTradeFeeRate(u64),
+    /// The flash loan fee rate, denominated in hundredths of a bip (10^-6)
+    FlashFeeRate(u64),
+}
+
+impl AmmConfigUpdate {
+    /// Return true for the updates the fee manager is allowed to make
+    pub fn is_fee_update(&self) -> bool {
+        !matches!(
+            self,
+            AmmConfigUpdate::ProtocolOwner(_)
+                | AmmConfigUpdate::FundOwner(_)
+                | AmmConfigUpdate::DisableCreatePool(_)
+        )
+    }
+
+    /// Return true for the updates which only make the config safer and so can skip the
+    /// timelock, every other update has to be queued
+    pub fn is_instant_update(&self) -> bool {
+        matches!(self, AmmConfigUpdate::DisableCreatePool(true))
+    }
+
+    /// The fixed size `(param, value, owner)` encoding stored by `PendingConfigUpdate`
+    fn to_param(self) -> (u8, u64, Pubkey) {
+        match self {
+            AmmConfigUpdate::TradeFeeRate(value) => (0, value, Pubkey::default()),
+            AmmConfigUpdate::ProtocolFeeRate(value) => (1, value, Pubkey::default()),
+            AmmConfigUpdate::FundFeeRate(value) => (2, value, Pubkey::default()),
+            AmmConfigUpdate::ProtocolOwner(owner) => (3, 0, owner),
+            AmmConfigUpdate::FundOwner(owner) => (4, 0, owner),
+            AmmConfigUpdate::CreatePoolFee(value) => (5, value, Pubkey::default()),
+            AmmConfigUpdate::DisableCreatePool(value) => (6, u64::from(value), Pubkey::default()),
+            AmmConfigUpdate::CreatorFeeRate(value) => (7, value, Pubkey::default()),
+            AmmConfigUpdate::MinTradeFeeRate(value) => (8, value, Pubkey::default()),
+            AmmConfigUpdate::MaxTradeFeeRate(value) => (9, value, Pubkey::default()),
+            AmmConfigUpdate::FlashFeeRate(value) => (10, value, Pubkey::default()),
+        }
+    }
+
+    fn from_param(param: u8, value: u64, owner: Pubkey) -> Result<Self> {
+        match param {
+            0 => Ok(AmmConfigUpdate::TradeFeeRate(value)),
+            1 => Ok(AmmConfigUpdate::ProtocolFeeRate(value)),
+            2 => Ok(AmmConfigUpdate::FundFeeRate(value)),
+            3 => Ok(AmmConfigUpdate::ProtocolOwner(owner)),
+            4 => Ok(AmmConfigUpdate::FundOwner(owner)),
+            5 => Ok(AmmConfigUpdate::CreatePoolFee(value)),
+            6 => Ok(AmmConfigUpdate::DisableCreatePool(value != 0)),
+            7 => Ok(AmmConfigUpdate::CreatorFeeRate(value)),
+            8 => Ok(AmmConfigUpdate::MinTradeFeeRate(value)),
+            9 => Ok(AmmConfigUpdate::MaxTradeFeeRate(value)),
+            10 => Ok(AmmConfigUpdate::FlashFeeRate(value)),
+            _ => Err(ErrorCode::InvalidInput.into()),
+        }
+    }
+}
+
+/// An amm config update waiting for its activation time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct PendingConfigUpdate {
+    /// The timestamp from which anyone can execute the update, zero when nothing is queued
+    pub activation_timestamp: u64,
+    /// The new value of the parameter
+    pub value: u64,
+    /// The new owner of the owner parameters
+    pub new_owner: Pubkey,
+    /// The parameter to update, in the order of the `AmmConfigUpdate` variants
+    pub param: u8,
+    pub padding: [u8; 7],
+}
+
+impl PendingConfigUpdate {
+    pub const LEN: usize = 8 + 8 + 32 + 1 + 7;
+
+    pub fn new(update: AmmConfigUpdate, activation_timestamp: u64) -> Self {
+        let (param, value, new_owner) = update.to_param();
+        Self {
+            activation_timestamp,
+            value,
+            new_owner,
+            param,
+            padding: [0u8; 7],
+        }
+    }
+
+    pub fn is_queued(&self) -> bool {
+        self.activation_timestamp != 0
+    }
+
+    pub fn update(&self) -> Result<AmmConfigUpdate> {
+        AmmConfigUpdate::from_param(self.param, self.value, self.new_owner)
+    }
+}
 
 /// Holds the current owner of the factory
 #[account]
@@ -26,10 +147,130 @@ pub struct AmmConfig {
     pub fund_owner: Pubkey,
     /// The pool creator fee, denominated in hundredths of a bip (10^-6)
     pub creator_fee_rate: u64,
+    /// Lower bound of the dynamic trade fee, denominated in hundredths of a bip (10^-6)
+    pub min_trade_fee_rate: u64,
+    /// Upper bound of the dynamic trade fee, denominated in hundredths of a bip (10^-6)
+    /// The trade fee is dynamic if it is not zero
+    pub max_trade_fee_rate: u64,
+    /// The flash loan fee, denominated in hundredths of a bip (10^-6)
+    pub flash_fee_rate: u64,
+    /// The update waiting to be executed after its timelock
+    pub pending_update: PendingConfigUpdate,
+    /// Bitwise representation of the state of every pool of the config,
+    /// with the same bits as `PoolState::status`
+    pub status: u8,
+    pub padding1: [u8; 7],
     /// padding
-    pub padding: [u64; 15],
+    pub padding: [u64; 4],
 }
 
 impl AmmConfig {
-    pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 + 8 * 15;
+    pub const LEN: usize =
+        8 + 1 + 1 + 2 + 4 * 8 + 32 * 2 + 8 + 3 * 8 + PendingConfigUpdate::LEN + 1 + 7 + 8 * 4;
+
+    /// Get status by bit, if it is `noraml` status, return true
+    pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
+        let status = 1u8 << (bit as u8);
+        self.status.bitand(status) == 0
+    }
+
+    /// The current value of the parameter `update` changes
+    pub fn current_value(&self, update: AmmConfigUpdate) -> AmmConfigUpdate {
+        match update {
+            AmmConfigUpdate::TradeFeeRate(_) => AmmConfigUpdate::TradeFeeRate(self.trade_fee_rate),
+            AmmConfigUpdate::ProtocolFeeRate(_) => {
+                AmmConfigUpdate::ProtocolFeeRate(self.protocol_fee_rate)
+            }
+            AmmConfigUpdate::FundFeeRate(_) => AmmConfigUpdate::FundFeeRate(self.fund_fee_rate),
+            AmmConfigUpdate::ProtocolOwner(_) => {
+                AmmConfigUpdate::ProtocolOwner(self.protocol_owner)
+            }
+            AmmConfigUpdate::FundOwner(_) => AmmConfigUpdate::FundOwner(self.fund_owner),
+            AmmConfigUpdate::CreatePoolFee(_) => {
+                AmmConfigUpdate::CreatePoolFee(self.create_pool_fee)
+            }
+            AmmConfigUpdate::DisableCreatePool(_) => {
+                AmmConfigUpdate::DisableCreatePool(self.disable_create_pool)
+            }
+            AmmConfigUpdate::CreatorFeeRate(_) => {
+                AmmConfigUpdate::CreatorFeeRate(self.creator_fee_rate)
+            }
+            AmmConfigUpdate::MinTradeFeeRate(_) => {
+                AmmConfigUpdate::MinTradeFeeRate(self.min_trade_fee_rate)
+            }
+            AmmConfigUpdate::MaxTradeFeeRate(_) => {
+                AmmConfigUpdate::MaxTradeFeeRate(self.max_trade_fee_rate)
+            }
+            AmmConfigUpdate::FlashFeeRate(_) => AmmConfigUpdate::FlashFeeRate(self.flash_fee_rate),
+        }
+    }
+
+    pub fn is_dynamic_fee_on(&self) -> bool {
+        self.max_trade_fee_rate != 0
+    }
+
+    /// The trade fee rate charged by a swap. With the dynamic fee on, the price volatility
+    /// recorded by the oracle is added to the pool's `trade_fee_rate` and the sum is bounded
+    /// by `min_trade_fee_rate` and `max_trade_fee_rate`.
+    pub fn effective_trade_fee_rate<S, E>(
+        &self,
+        trade_fee_rate: u64,
+        observation_state: &ObservationRing<S, E>,
+        token_0_price_x32: u128,
+    ) -> u64
+    where
+        S: Deref<Target = ObservationState>,
+        E: Deref<Target = [Observation]>,
+    {
+        if !self.is_dynamic_fee_on() {
+            return trade_fee_rate;
+        }
+        trade_fee_rate
+            .saturating_add(observation_state.price_volatility(token_0_price_x32))
+            .max(self.min_trade_fee_rate)
+            .min(self.max_trade_fee_rate)
+    }
+}
+
+#[cfg(test)]
+pub mod config_test {
+    use super::*;
+
+    #[test]
+    fn pending_config_update_round_trip_test() {
+        let owner = Pubkey::new_unique();
+        for update in [
+            AmmConfigUpdate::TradeFeeRate(2500),
+            AmmConfigUpdate::ProtocolOwner(owner),
+            AmmConfigUpdate::DisableCreatePool(true),
+            AmmConfigUpdate::FlashFeeRate(900),
+        ] {
+            let pending_update = PendingConfigUpdate::new(update, 1);
+            assert!(pending_update.is_queued());
+            assert_eq!(pending_update.update().unwrap(), update);
+        }
+        assert!(PendingConfigUpdate {
+            param: 11,
+            ..Default::default()
+        }
+        .update()
+        .is_err());
+    }
+
+    #[test]
+    fn current_value_test() {
+        let amm_config = AmmConfig {
+            trade_fee_rate: 2500,
+            disable_create_pool: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            amm_config.current_value(AmmConfigUpdate::TradeFeeRate(100)),
+            AmmConfigUpdate::TradeFeeRate(2500)
+        );
+        assert_eq!(
+            amm_config.current_value(AmmConfigUpdate::DisableCreatePool(false)),
+            AmmConfigUpdate::DisableCreatePool(true)
+        );
+    }
 }
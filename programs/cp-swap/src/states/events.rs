@@ -1,3 +1,4 @@
+use crate::states::{AmmConfigUpdate, GlobalRole, PoolFeeRateUpdate};
 use anchor_lang::prelude::*;
 
 /// Emitted when deposit and withdraw
@@ -42,4 +43,110 @@ pub struct SwapEvent {
     /// Amount of fee tokens going to creator
     pub creator_fee: u64,
     pub creator_fee_on_input: bool,
+    /// The trade fee rate the swap was charged, which varies with the dynamic fee
+    pub trade_fee_rate: u64,
+    /// The part of the input amount a price limit swap left with the user
+    pub unfilled_amount: u64,
+}
+
+/// Emitted when flash loan
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct FlashLoanEvent {
+    pub pool_id: Pubkey,
+    pub borrower_program: Pubkey,
+    /// borrowed amount without transfer fee
+    pub amount_0: u64,
+    /// borrowed amount without transfer fee
+    pub amount_1: u64,
+    pub flash_fee_0: u64,
+    pub flash_fee_1: u64,
+}
+
+/// Emitted when a pool is moved to another amm config
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolConfigMigratedEvent {
+    pub pool_id: Pubkey,
+    pub old_amm_config: Pubkey,
+    pub new_amm_config: Pubkey,
+    /// The pool trade fee rate under the old config, before any dynamic fee
+    pub old_trade_fee_rate: u64,
+    /// The pool trade fee rate under the new config, before any dynamic fee
+    pub new_trade_fee_rate: u64,
+}
+
+/// Emitted when an amm config update is queued
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AmmConfigUpdateQueuedEvent {
+    pub amm_config: Pubkey,
+    pub update: AmmConfigUpdate,
+    pub activation_timestamp: u64,
+}
+
+/// Emitted when a queued amm config update is executed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AmmConfigUpdateExecutedEvent {
+    pub amm_config: Pubkey,
+    pub update: AmmConfigUpdate,
+}
+
+/// Emitted when a queued amm config update is cancelled
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AmmConfigUpdateCancelledEvent {
+    pub amm_config: Pubkey,
+    pub update: AmmConfigUpdate,
+}
+
+/// Emitted when a parameter of an amm config is updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AmmConfigUpdated {
+    pub amm_config: Pubkey,
+    /// The parameter with its value before the update
+    pub old_value: AmmConfigUpdate,
+    /// The parameter with its value after the update
+    pub new_value: AmmConfigUpdate,
+}
+
+/// Emitted when a fee rate override of a pool is updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolFeeRateUpdated {
+    pub pool_id: Pubkey,
+    /// The fee rate with its override before the update
+    pub old_value: PoolFeeRateUpdate,
+    /// The fee rate with its override after the update
+    pub new_value: PoolFeeRateUpdate,
+}
+
+/// Emitted when the admin proposes a new admin, or cancels a pending transfer
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AdminTransferProposedEvent {
+    pub admin: Pubkey,
+    pub old_pending_admin: Pubkey,
+    /// The default pubkey when the pending transfer is cancelled
+    pub new_pending_admin: Pubkey,
+}
+
+/// Emitted when the pending admin accepts the admin role
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct AdminTransferredEvent {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+/// Emitted when the admin grants or revokes a role
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RoleUpdatedEvent {
+    pub role: GlobalRole,
+    pub old_authority: Pubkey,
+    /// The default pubkey when the role is revoked
+    pub new_authority: Pubkey,
 }
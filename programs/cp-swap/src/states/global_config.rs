@@ -0,0 +1,78 @@
+use crate::error::ErrorCode;
+use crate::states::PoolStatusBitIndex;
+use anchor_lang::prelude::*;
+use std::ops::BitAnd;
+
+pub const GLOBAL_CONFIG_SEED: &str = "global_config";
+
+/// The roles the admin can grant, each allowed a part of the admin instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlobalRole {
+    Guardian,
+    FeeManager,
+    ListingManager,
+}
+
+/// Holds the admin of the program
+#[account]
+#[derive(Default, Debug)]
+pub struct GlobalConfig {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The admin allowed to call the admin instructions
+    pub admin: Pubkey,
+    /// The admin proposed by `transfer_admin`, default when no transfer is pending
+    pub pending_admin: Pubkey,
+    /// Can only disable pool status bits via `update_pool_status`
+    pub guardian: Pubkey,
+    /// Can update the fee params of amm configs via `queue_amm_config_update`
+    pub fee_manager: Pubkey,
+    /// Can create and close support mint associated accounts
+    pub listing_manager: Pubkey,
+    /// Bitwise representation of the state of every pool, with the same bits as
+    /// `PoolState::status`
+    pub status: u8,
+    pub padding1: [u8; 7],
+    /// padding
+    pub padding: [u64; 3],
+}
+
+impl GlobalConfig {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 32 * 3 + 1 + 7 + 8 * 3;
+
+    /// Get status by bit, if it is `noraml` status, return true
+    pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
+        let status = 1u8 << (bit as u8);
+        self.status.bitand(status) == 0
+    }
+
+    /// The admin can set any status, the guardian can only disable more bits
+    pub fn check_status_authority(
+        &self,
+        authority: Pubkey,
+        old_status: u8,
+        new_status: u8,
+    ) -> Result<()> {
+        if authority != self.admin {
+            require_keys_eq!(authority, self.guardian, ErrorCode::InvalidOwner);
+            require_eq!(new_status & old_status, old_status, ErrorCode::NotApproved);
+        }
+        Ok(())
+    }
+
+    pub fn role_authority(&self, role: GlobalRole) -> Pubkey {
+        match role {
+            GlobalRole::Guardian => self.guardian,
+            GlobalRole::FeeManager => self.fee_manager,
+            GlobalRole::ListingManager => self.listing_manager,
+        }
+    }
+
+    pub fn set_role(&mut self, role: GlobalRole, authority: Pubkey) {
+        match role {
+            GlobalRole::Guardian => self.guardian = authority,
+            GlobalRole::FeeManager => self.fee_manager = authority,
+            GlobalRole::ListingManager => self.listing_manager = authority,
+        }
+    }
+}
@@ -1,7 +1,12 @@
+use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
 use crate::error::ErrorCode;
+use crate::utils::U256;
 /// Oracle provides price data useful for a wide variety of system designs
 ///
 use anchor_lang::prelude::*;
+use std::cell::{Ref, RefMut};
+use std::mem::size_of;
+use std::ops::{Deref, DerefMut};
 #[cfg(test)]
 use std::time::{SystemTime, UNIX_EPOCH};
 /// Seed to derive account address and signature
@@ -9,6 +14,12 @@ pub const OBSERVATION_SEED: &str = "observation";
 // Number of ObservationState element
 pub const OBSERVATION_NUM: usize = 100;
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
+/// The maximum number of observations an ObservationState can be grown to
+pub const OBSERVATION_NUM_MAX: usize = 2_000;
+/// The maximum update duration of a pool's observations
+pub const OBSERVATION_UPDATE_DURATION_MAX: u64 = 3_600;
+/// Number of observations the price volatility of the dynamic trade fee looks back on
+pub const VOLATILITY_OBSERVATION_NUM: usize = 10;
 
 /// The element of observations in ObservationState
 #[zero_copy(unsafe)]
@@ -25,6 +36,10 @@ pub struct Observation {
 impl Observation {
     pub const LEN: usize = 8 + 16 + 16;
 }
+// Safety: a packed struct of integers has no padding and any bit pattern is valid,
+// the observations past `OBSERVATION_NUM` are cast from the account data
+unsafe impl bytemuck::Zeroable for Observation {}
+unsafe impl bytemuck::Pod for Observation {}
 
 #[account(zero_copy(unsafe))]
 #[repr(C, packed)]
@@ -39,8 +54,12 @@ pub struct ObservationState {
     pub observations: [Observation; OBSERVATION_NUM],
     /// the last update timestamp
     pub last_update_timestamp: u64,
+    /// the number of observations of a grown account, zero for `OBSERVATION_NUM`
+    pub observation_num: u64,
+    /// the minimum time between two observations, zero for `OBSERVATION_UPDATE_DURATION_DEFAULT`
+    pub update_duration: u64,
     /// padding for feature update
-    pub padding: [u64; 3],
+    pub padding: [u64; 1],
 }
 
 impl Default for ObservationState {
@@ -52,7 +71,9 @@ impl Default for ObservationState {
             pool_id: Pubkey::default(),
             observations: [Observation::default(); OBSERVATION_NUM],
             last_update_timestamp: 0,
-            padding: [0u64; 3],
+            observation_num: 0,
+            update_duration: 0,
+            padding: [0u64; 1],
         }
     }
 }
@@ -60,9 +81,271 @@ impl Default for ObservationState {
 impl ObservationState {
     pub const LEN: usize = 8 + 1 + 2 + 32 + (Observation::LEN * OBSERVATION_NUM) + 8 * 4;
 
+    /// The account space for a ring of `observation_num` observations
+    pub fn space(observation_num: usize) -> usize {
+        Self::LEN + observation_num.saturating_sub(OBSERVATION_NUM) * Observation::LEN
+    }
+
+    /// The number of observations in the ring, accounts that were never grown hold `OBSERVATION_NUM`
+    pub fn observation_num(&self) -> usize {
+        if self.observation_num == 0 {
+            OBSERVATION_NUM
+        } else {
+            self.observation_num as usize
+        }
+    }
+
+    /// The minimum time between two observations
+    pub fn observation_update_duration(&self) -> u64 {
+        if self.update_duration == 0 {
+            OBSERVATION_UPDATE_DURATION_DEFAULT
+        } else {
+            self.update_duration
+        }
+    }
+
+    /// The span of time the observations are sure to cover once the ring is full
+    pub fn history_duration(&self) -> u64 {
+        (self.observation_num() as u64 - 1) * self.observation_update_duration()
+    }
+}
+
+/// The observations of an ObservationState account. The observations past the first
+/// `OBSERVATION_NUM` of a grown account are stored right after the ObservationState,
+/// `extension` holds them.
+pub struct ObservationRing<S, E> {
+    pub state: S,
+    pub extension: E,
+}
+
+impl<S, E> ObservationRing<S, E>
+where
+    S: Deref<Target = ObservationState>,
+    E: Deref<Target = [Observation]>,
+{
+    /// The number of observations in the ring
+    pub fn observation_num(&self) -> usize {
+        OBSERVATION_NUM + self.extension.len()
+    }
+
+    pub fn observation(&self, index: usize) -> Observation {
+        if index < OBSERVATION_NUM {
+            self.state.observations[index]
+        } else {
+            self.extension[index - OBSERVATION_NUM]
+        }
+    }
+
+    /// Relative deviation of the token_0 spot price from its time weighted average over the
+    /// last `VOLATILITY_OBSERVATION_NUM` observations, denominated in hundredths of a bip (10^-6).
+    /// Zero while the oracle has not recorded any time span yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_0_price_x32` - The current token_0 spot price
+    ///
+    pub fn price_volatility(&self, token_0_price_x32: u128) -> u64 {
+        let observation_num = self.observation_num();
+        let observation_index = self.state.observation_index as usize;
+        let last_observation = self.observation(observation_index);
+        // Walk back to the oldest recorded observation within the lookback window
+        let mut start_observation = last_observation;
+        for lookback in 1..=VOLATILITY_OBSERVATION_NUM.min(observation_num - 1) {
+            let observation = self
+                .observation((observation_index + observation_num - lookback) % observation_num);
+            if observation.block_timestamp == 0
+                || observation.block_timestamp >= start_observation.block_timestamp
+            {
+                break;
+            }
+            start_observation = observation;
+        }
+        // The cumulative price of the last observation is accumulated up to the last update
+        let duration = self
+            .state
+            .last_update_timestamp
+            .saturating_sub(start_observation.block_timestamp);
+        if duration == 0 {
+            return 0;
+        }
+        let average_price_x32 = last_observation
+            .cumulative_token_0_price_x32
+            .wrapping_sub(start_observation.cumulative_token_0_price_x32)
+            / u128::from(duration);
+        if average_price_x32 == 0 {
+            return 0;
+        }
+        let deviation = token_0_price_x32.abs_diff(average_price_x32);
+        u64::try_from(
+            deviation.saturating_mul(u128::from(FEE_RATE_DENOMINATOR_VALUE)) / average_price_x32,
+        )
+        .unwrap_or(u64::MAX)
+    }
+
+    /// The timestamp of the oldest observation, none while the oracle is not initialized
+    pub fn oldest_observation_timestamp(&self) -> Option<u64> {
+        if self.state.initialized {
+            Some(self.observation_at(0).0)
+        } else {
+            None
+        }
+    }
+
+    fn oldest_observation_index(&self) -> usize {
+        let next_observation_index =
+            (self.state.observation_index as usize + 1) % self.observation_num();
+        if self.observation(next_observation_index).block_timestamp == 0 {
+            0
+        } else {
+            next_observation_index
+        }
+    }
+
+    fn observation_count(&self) -> usize {
+        let observation_num = self.observation_num();
+        (self.state.observation_index as usize + observation_num - self.oldest_observation_index())
+            % observation_num
+            + 1
+    }
+
+    /// The `position`th oldest observation, along with the timestamp its cumulative
+    /// prices are accumulated up to. The cumulative prices of the most recent
+    /// observation keep accumulating until the next observation is written, older
+    /// ones are taken at their own timestamp, which is accurate to the update duration.
+    fn observation_at(&self, position: usize) -> (u64, Observation) {
+        let index = (self.oldest_observation_index() + position) % self.observation_num();
+        let observation = self.observation(index);
+        let timestamp = if index == self.state.observation_index as usize {
+            self.state
+                .last_update_timestamp
+                .max(observation.block_timestamp)
+        } else {
+            observation.block_timestamp
+        };
+        (timestamp, observation)
+    }
+
+    /// The cumulative prices `seconds_ago` before `block_timestamp`, interpolated between the
+    /// observations around that time. Past the most recent observation, the cumulative prices
+    /// are extrapolated with the current prices. The cumulative prices wrap, only the
+    /// difference between two of them is meaningful.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_timestamp` - The current timestamp
+    /// * `seconds_ago` - How long before `block_timestamp` to observe
+    /// * `token_0_price_x32` - The current token_0 price
+    /// * `token_1_price_x32` - The current token_1 price
+    ///
+    pub fn observe(
+        &self,
+        block_timestamp: u64,
+        seconds_ago: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) -> Result<(u128, u128)> {
+        require!(self.state.initialized, ErrorCode::ObservationNotAvailable);
+        let target_timestamp = block_timestamp
+            .checked_sub(seconds_ago)
+            .ok_or(ErrorCode::ObservationNotAvailable)?;
+        let observation_count = self.observation_count();
+        let (latest_timestamp, latest_observation) = self.observation_at(observation_count - 1);
+        if target_timestamp >= latest_timestamp {
+            let elapsed = u128::from(target_timestamp - latest_timestamp);
+            return Ok((
+                latest_observation
+                    .cumulative_token_0_price_x32
+                    .wrapping_add(token_0_price_x32.wrapping_mul(elapsed)),
+                latest_observation
+                    .cumulative_token_1_price_x32
+                    .wrapping_add(token_1_price_x32.wrapping_mul(elapsed)),
+            ));
+        }
+        require_gte!(
+            target_timestamp,
+            self.observation_at(0).0,
+            ErrorCode::ObservationNotAvailable
+        );
+
+        // Find the observations at or before and after the target timestamp
+        let (mut before, mut after) = (0, observation_count - 1);
+        while after - before > 1 {
+            let middle = before + (after - before) / 2;
+            if self.observation_at(middle).0 <= target_timestamp {
+                before = middle;
+            } else {
+                after = middle;
+            }
+        }
+        let (before_timestamp, before_observation) = self.observation_at(before);
+        let (after_timestamp, after_observation) = self.observation_at(after);
+        let elapsed = U256::from(target_timestamp - before_timestamp);
+        let duration = U256::from(after_timestamp - before_timestamp);
+        let interpolate = |before_cumulative: u128, after_cumulative: u128| -> u128 {
+            let delta =
+                U256::from(after_cumulative.wrapping_sub(before_cumulative)) * elapsed / duration;
+            before_cumulative.wrapping_add(delta.as_u128())
+        };
+        Ok((
+            interpolate(
+                before_observation.cumulative_token_0_price_x32,
+                after_observation.cumulative_token_0_price_x32,
+            ),
+            interpolate(
+                before_observation.cumulative_token_1_price_x32,
+                after_observation.cumulative_token_1_price_x32,
+            ),
+        ))
+    }
+
+    /// The time weighted average prices over the last `window` seconds before `block_timestamp`
+    ///
+    /// # Arguments
+    ///
+    /// * `block_timestamp` - The current timestamp
+    /// * `window` - The length of the averaging period in seconds
+    /// * `token_0_price_x32` - The current token_0 price
+    /// * `token_1_price_x32` - The current token_1 price
+    ///
+    pub fn consult_twap(
+        &self,
+        block_timestamp: u64,
+        window: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+    ) -> Result<(u128, u128)> {
+        require_gt!(window, 0, ErrorCode::InvalidInput);
+        let (end_token_0_cumulative, end_token_1_cumulative) =
+            self.observe(block_timestamp, 0, token_0_price_x32, token_1_price_x32)?;
+        let (start_token_0_cumulative, start_token_1_cumulative) = self.observe(
+            block_timestamp,
+            window,
+            token_0_price_x32,
+            token_1_price_x32,
+        )?;
+        Ok((
+            end_token_0_cumulative.wrapping_sub(start_token_0_cumulative) / u128::from(window),
+            end_token_1_cumulative.wrapping_sub(start_token_1_cumulative) / u128::from(window),
+        ))
+    }
+}
+
+impl<S, E> ObservationRing<S, E>
+where
+    S: DerefMut<Target = ObservationState>,
+    E: DerefMut<Target = [Observation]>,
+{
+    fn observation_mut(&mut self, index: usize) -> &mut Observation {
+        if index < OBSERVATION_NUM {
+            &mut self.state.observations[index]
+        } else {
+            &mut self.extension[index - OBSERVATION_NUM]
+        }
+    }
+
     // Writes an oracle observation to the account, returning the next observation_index.
     /// Writable at most once per second. Index represents the most recently written element.
-    /// If the index is at the end of the allowable array length (100 - 1), the next index will turn to 0.
+    /// If the index is at the end of the allowable array length (observation_num - 1), the next index will turn to 0.
     ///
     /// # Arguments
     ///
@@ -81,30 +364,32 @@ impl ObservationState {
         token_0_price_x32: u128,
         token_1_price_x32: u128,
     ) -> Result<()> {
-        let observation_index = self.observation_index;
-        if !self.initialized {
+        let observation_index = self.state.observation_index;
+        if !self.state.initialized {
             // skip the pool init price
-            self.initialized = true;
-            self.observations[observation_index as usize].block_timestamp = block_timestamp;
-            self.observations[observation_index as usize].cumulative_token_0_price_x32 = 0;
-            self.observations[observation_index as usize].cumulative_token_1_price_x32 = 0;
-            self.last_update_timestamp = block_timestamp;
+            self.state.initialized = true;
+            let observation = self.observation_mut(observation_index as usize);
+            observation.block_timestamp = block_timestamp;
+            observation.cumulative_token_0_price_x32 = 0;
+            observation.cumulative_token_1_price_x32 = 0;
+            self.state.last_update_timestamp = block_timestamp;
             return Ok(());
         }
-        let last_observation = self.observations[observation_index as usize];
-        let next_observation_index = if observation_index as usize == OBSERVATION_NUM - 1 {
+        let last_observation = self.observation(observation_index as usize);
+        let next_observation_index = if observation_index as usize == self.observation_num() - 1 {
             0
         } else {
             observation_index + 1
         };
         // Ensure last_update_timestamp is set for legacy accounts
-        if self.last_update_timestamp == 0 {
-            self.last_update_timestamp = last_observation.block_timestamp;
+        if self.state.last_update_timestamp == 0 {
+            self.state.last_update_timestamp = last_observation.block_timestamp;
         }
         let time_since_last_observation =
             block_timestamp.saturating_sub(last_observation.block_timestamp);
         // Accumulate using last known price over the elapsed time
-        let time_since_last_update = block_timestamp.saturating_sub(self.last_update_timestamp);
+        let time_since_last_update =
+            block_timestamp.saturating_sub(self.state.last_update_timestamp);
         if time_since_last_update == 0 || time_since_last_observation == 0 {
             return Ok(());
         }
@@ -114,31 +399,122 @@ impl ObservationState {
         let delta_token_1_price_x32 = token_1_price_x32
             .checked_mul(time_since_last_update.into())
             .ok_or(ErrorCode::MathOverflow)?;
-        if time_since_last_observation < OBSERVATION_UPDATE_DURATION_DEFAULT {
-            self.observations[observation_index as usize].cumulative_token_0_price_x32 =
-                last_observation
-                    .cumulative_token_0_price_x32
-                    .wrapping_add(delta_token_0_price_x32);
-            self.observations[observation_index as usize].cumulative_token_1_price_x32 =
-                last_observation
-                    .cumulative_token_1_price_x32
-                    .wrapping_add(delta_token_1_price_x32);
+        if time_since_last_observation < self.state.observation_update_duration() {
+            let observation = self.observation_mut(observation_index as usize);
+            observation.cumulative_token_0_price_x32 = last_observation
+                .cumulative_token_0_price_x32
+                .wrapping_add(delta_token_0_price_x32);
+            observation.cumulative_token_1_price_x32 = last_observation
+                .cumulative_token_1_price_x32
+                .wrapping_add(delta_token_1_price_x32);
         } else {
-            self.observations[next_observation_index as usize].block_timestamp = block_timestamp;
+            let observation = self.observation_mut(next_observation_index as usize);
+            observation.block_timestamp = block_timestamp;
             // cumulative_token_price_x32 only occupies the first 64 bits, and the remaining 64 bits are used to store overflow data
-            self.observations[next_observation_index as usize].cumulative_token_0_price_x32 =
-                last_observation
-                    .cumulative_token_0_price_x32
-                    .wrapping_add(delta_token_0_price_x32);
-            self.observations[next_observation_index as usize].cumulative_token_1_price_x32 =
-                last_observation
-                    .cumulative_token_1_price_x32
-                    .wrapping_add(delta_token_1_price_x32);
-            self.observation_index = next_observation_index;
+            observation.cumulative_token_0_price_x32 = last_observation
+                .cumulative_token_0_price_x32
+                .wrapping_add(delta_token_0_price_x32);
+            observation.cumulative_token_1_price_x32 = last_observation
+                .cumulative_token_1_price_x32
+                .wrapping_add(delta_token_1_price_x32);
+            self.state.observation_index = next_observation_index;
         }
-        self.last_update_timestamp = block_timestamp;
+        self.state.last_update_timestamp = block_timestamp;
         Ok(())
     }
+
+    /// Rotates the observations so the oldest one is first and the most recent one last,
+    /// new observations appended to the ring then follow the most recent one
+    pub fn linearize(&mut self) {
+        let oldest_observation_index = self.oldest_observation_index();
+        if oldest_observation_index == 0 {
+            return;
+        }
+        let observation_num = self.observation_num();
+        self.reverse(0, oldest_observation_index);
+        self.reverse(oldest_observation_index, observation_num);
+        self.reverse(0, observation_num);
+        self.state.observation_index = u16::try_from(observation_num - 1).unwrap();
+    }
+
+    fn reverse(&mut self, mut start: usize, mut end: usize) {
+        while start + 1 < end {
+            end -= 1;
+            let observation = self.observation(start);
+            *self.observation_mut(start) = self.observation(end);
+            *self.observation_mut(end) = observation;
+            start += 1;
+        }
+    }
+}
+
+impl<'a> ObservationRing<&'a ObservationState, &'a [Observation]> {
+    /// Reads the observations from the data of an ObservationState account
+    pub fn try_from_account_data(data: &'a [u8]) -> Result<Self> {
+        if !data.starts_with(ObservationState::DISCRIMINATOR) {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if data.len() < ObservationState::LEN {
+            return err!(anchor_lang::error::ErrorCode::AccountDidNotDeserialize);
+        }
+        let (state, extension) = split_observations(&data[ObservationState::DISCRIMINATOR.len()..]);
+        Ok(ObservationRing { state, extension })
+    }
+}
+
+fn split_observations(data: &[u8]) -> (&ObservationState, &[Observation]) {
+    let (state, extension) = data.split_at(size_of::<ObservationState>());
+    let state: &ObservationState = bytemuck::from_bytes(state);
+    let extension_len = state.observation_num() - OBSERVATION_NUM;
+    (
+        state,
+        bytemuck::cast_slice(&extension[..extension_len * Observation::LEN]),
+    )
+}
+
+fn split_observations_mut(data: &mut [u8]) -> (&mut ObservationState, &mut [Observation]) {
+    let (state, extension) = data.split_at_mut(size_of::<ObservationState>());
+    let state: &mut ObservationState = bytemuck::from_bytes_mut(state);
+    let extension_len = state.observation_num() - OBSERVATION_NUM;
+    (
+        state,
+        bytemuck::cast_slice_mut(&mut extension[..extension_len * Observation::LEN]),
+    )
+}
+
+/// Loads all the observations of an ObservationState account
+pub fn load_observations<'a>(
+    observation_state: &'a AccountLoader<ObservationState>,
+) -> Result<ObservationRing<Ref<'a, ObservationState>, Ref<'a, [Observation]>>> {
+    // Checks the discriminator
+    drop(observation_state.load()?);
+    let data = observation_state.as_ref().try_borrow_data()?;
+    let (state, extension) = Ref::map_split(data, |data| {
+        split_observations(&data[ObservationState::DISCRIMINATOR.len()..])
+    });
+    Ok(ObservationRing { state, extension })
+}
+
+/// Mutably loads all the observations of an ObservationState account
+pub fn load_observations_mut<'a>(
+    observation_state: &'a AccountLoader<ObservationState>,
+) -> Result<ObservationRing<RefMut<'a, ObservationState>, RefMut<'a, [Observation]>>> {
+    // Checks the discriminator and that the account is writable
+    drop(observation_state.load_mut()?);
+    let data = observation_state.as_ref().try_borrow_mut_data()?;
+    let (state, extension) = RefMut::map_split(data, |data| {
+        split_observations_mut(&mut data[ObservationState::DISCRIMINATOR.len()..])
+    });
+    Ok(ObservationRing { state, extension })
+}
+
+/// The time weighted average prices returned to CPI callers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct TwapPrice {
+    /// The average token_0 price, Q32.32
+    pub token_0_price_x32: u128,
+    /// The average token_1 price, Q32.32
+    pub token_1_price_x32: u128,
 }
 
 /// Returns the block timestamp truncated to 32 bits, i.e. mod 2**32
@@ -166,4 +542,202 @@ pub mod observation_test {
             ObservationState::LEN - 8
         )
     }
+
+    #[test]
+    fn price_volatility_test() {
+        let mut state = ObservationState::default();
+        let mut observation_state = ObservationRing {
+            state: &mut state,
+            extension: &mut [][..],
+        };
+        let price_x32 = 100u128 << 32;
+        observation_state
+            .update(1_000, price_x32, price_x32)
+            .unwrap();
+        assert_eq!(observation_state.price_volatility(price_x32), 0);
+        // a constant price over the window
+        for i in 1..=20 {
+            observation_state
+                .update(1_000 + i * 15, price_x32, price_x32)
+                .unwrap();
+        }
+        assert_eq!(observation_state.price_volatility(price_x32), 0);
+        // 5% above the average
+        assert_eq!(observation_state.price_volatility(105u128 << 32), 50_000);
+        // the average catches up with a new price
+        for i in 21..=40 {
+            observation_state
+                .update(1_000 + i * 15, price_x32 * 2, price_x32 / 2)
+                .unwrap();
+        }
+        assert_eq!(observation_state.price_volatility(price_x32 * 2), 0);
+    }
+
+    #[test]
+    fn consult_twap_test() {
+        let mut state = ObservationState::default();
+        let mut observation_state = ObservationRing {
+            state: &mut state,
+            extension: &mut [][..],
+        };
+        let price_x32 = 100u128 << 32;
+        assert!(observation_state
+            .consult_twap(1_000, 15, price_x32, price_x32)
+            .is_err());
+        observation_state
+            .update(1_000, price_x32, price_x32)
+            .unwrap();
+        // the accumulators are close to wrapping around
+        observation_state.state.observations[0].cumulative_token_0_price_x32 =
+            u128::MAX - price_x32;
+        observation_state.state.observations[0].cumulative_token_1_price_x32 = u128::MAX;
+        // wrap around the observation buffer more than once
+        let mut block_timestamp = 1_000;
+        for _ in 0..250 {
+            block_timestamp += 15;
+            observation_state
+                .update(block_timestamp, price_x32, price_x32 / 2)
+                .unwrap();
+        }
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp, 1_000, price_x32, price_x32 / 2)
+                .unwrap(),
+            (price_x32, price_x32 / 2)
+        );
+        // the history only covers the last 99 durations
+        assert!(observation_state
+            .consult_twap(block_timestamp, 99 * 15, price_x32, price_x32 / 2)
+            .is_ok());
+        assert!(observation_state
+            .consult_twap(block_timestamp, 99 * 15 + 1, price_x32, price_x32 / 2)
+            .is_err());
+
+        for _ in 0..20 {
+            block_timestamp += 15;
+            observation_state
+                .update(block_timestamp, price_x32 * 2, price_x32)
+                .unwrap();
+        }
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp, 150, price_x32 * 2, price_x32)
+                .unwrap(),
+            (price_x32 * 2, price_x32)
+        );
+        // half of the window at each price, in between observations
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp, 600, price_x32 * 2, price_x32)
+                .unwrap(),
+            (price_x32 * 3 / 2, price_x32 * 3 / 4)
+        );
+        assert_eq!(
+            observation_state
+                .observe(block_timestamp, 307, price_x32 * 2, price_x32)
+                .unwrap()
+                .0
+                .wrapping_sub(
+                    observation_state
+                        .observe(block_timestamp, 308, price_x32 * 2, price_x32)
+                        .unwrap()
+                        .0
+                ),
+            price_x32
+        );
+        // extrapolated with the current price since the last update
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp + 10, 10, price_x32 * 4, price_x32)
+                .unwrap(),
+            (price_x32 * 4, price_x32)
+        );
+    }
+
+    #[test]
+    fn grow_observations_test() {
+        let mut state = ObservationState::default();
+        let mut extension = [Observation::default(); 100];
+        let price_x32 = 100u128 << 32;
+        let mut block_timestamp = 1_000;
+        {
+            let mut observation_state = ObservationRing {
+                state: &mut state,
+                extension: &mut extension[..0],
+            };
+            observation_state
+                .update(block_timestamp, price_x32, price_x32)
+                .unwrap();
+            for _ in 0..150 {
+                block_timestamp += 15;
+                observation_state
+                    .update(block_timestamp, price_x32, price_x32)
+                    .unwrap();
+            }
+            assert_eq!({ observation_state.state.observation_index }, 50);
+            observation_state.linearize();
+            assert_eq!({ observation_state.state.observation_index }, 99);
+            for index in 0..100 {
+                assert_eq!(
+                    { observation_state.observation(index).block_timestamp },
+                    1_000 + (51 + index as u64) * 15
+                );
+            }
+        }
+
+        assert_eq!(state.history_duration(), 99 * 15);
+
+        // grow the ring to 200 observations with a 60 seconds update duration
+        state.observation_num = 200;
+        state.update_duration = 60;
+        assert_eq!(state.history_duration(), 199 * 60);
+        let mut observation_state = ObservationRing {
+            state: &mut state,
+            extension: &mut extension[..],
+        };
+        assert_eq!(observation_state.observation_num(), 200);
+        assert!(observation_state
+            .consult_twap(block_timestamp, 99 * 15, price_x32, price_x32)
+            .is_ok());
+        assert!(observation_state
+            .consult_twap(block_timestamp, 99 * 15 + 1, price_x32, price_x32)
+            .is_err());
+        for _ in 0..150 {
+            block_timestamp += 60;
+            observation_state
+                .update(block_timestamp, price_x32 * 2, price_x32)
+                .unwrap();
+        }
+        // the observations are written to the extension and wrap around the whole ring
+        assert_eq!({ observation_state.state.observation_index }, 49);
+        assert_eq!(
+            { observation_state.observation(199).block_timestamp },
+            block_timestamp - 50 * 60
+        );
+        // a twap over more than two hours
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp, 150 * 60, price_x32 * 2, price_x32)
+                .unwrap(),
+            (price_x32 * 2, price_x32)
+        );
+        assert_eq!(
+            observation_state
+                .consult_twap(block_timestamp, 150 * 60 + 600, price_x32 * 2, price_x32)
+                .unwrap(),
+            (price_x32 * 31 / 16, price_x32)
+        );
+        // the oldest observation left from before the ring was grown
+        assert!(observation_state
+            .consult_twap(block_timestamp, 150 * 60 + 49 * 15, price_x32, price_x32)
+            .is_ok());
+        assert!(observation_state
+            .consult_twap(
+                block_timestamp,
+                150 * 60 + 49 * 15 + 1,
+                price_x32,
+                price_x32
+            )
+            .is_err());
+    }
 }
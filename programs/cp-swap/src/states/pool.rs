@@ -1,20 +1,48 @@
-use crate::{curve::TradeDirection, error::ErrorCode};
+use crate::{
+    curve::{
+        fees::{FeeRates, FEE_RATE_DENOMINATOR_VALUE},
+        reduce_weights, ConstantProductCurve, Curve, CurveParams, CurveType, OffsetCurve,
+        StableSwapCurve, TradeDirection, WeightedCurve,
+    },
+    error::ErrorCode,
+    states::{AmmConfig, GlobalConfig, Observation, ObservationRing, ObservationState},
+    utils::U256,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
-use std::ops::{BitAnd, BitOr, BitXor};
+use std::ops::{BitAnd, BitOr, BitXor, Deref};
 /// Seed to derive account address and signature
 pub const POOL_SEED: &str = "pool";
 pub const POOL_LP_MINT_SEED: &str = "pool_lp_mint";
 pub const POOL_VAULT_SEED: &str = "pool_vault";
 
 pub const Q32: u128 = (u32::MAX as u128) + 1; // 2^32
+pub const BPS_DENOMINATOR: u64 = 10_000;
 
+#[derive(Clone, Copy)]
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
     Swap,
 }
 
+pub enum PoolFeeOverrideBitIndex {
+    TradeFeeRate,
+    ProtocolFeeRate,
+    FundFeeRate,
+}
+
+/// A fee rate a pool overrides its amm config with, None falls back to the config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolFeeRateUpdate {
+    /// The trade fee rate, before any dynamic fee is added
+    TradeFeeRate(Option<u64>),
+    /// The protocol fee rate, a share of the trade fee
+    ProtocolFeeRate(Option<u64>),
+    /// The fund fee rate, a share of the trade fee
+    FundFeeRate(Option<u64>),
+}
+
 #[derive(PartialEq, Eq)]
 pub enum PoolStatusBitFlag {
     Enable,
@@ -51,6 +79,20 @@ impl CreatorFeeOn {
     }
 }
 
+/// The accounts a new pool is initialized with
+pub struct PoolAccounts<'a, 'info> {
+    /// Bump of the authority of the vaults and lp mint
+    pub auth_bump: u8,
+    pub pool_creator: Pubkey,
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_1_mint: &'a InterfaceAccount<'info, Mint>,
+    pub lp_mint: &'a InterfaceAccount<'info, Mint>,
+    pub observation_key: Pubkey,
+}
+
 pub struct SwapParams {
     pub trade_direction: TradeDirection,
     pub total_input_token_amount: u64,
@@ -121,47 +163,88 @@ pub struct PoolState {
     /// 2: only token_1 as trade fee
     pub creator_fee_on: u8,
     pub enable_creator_fee: bool,
-    pub padding1: [u8; 6],
+    /// The curve invariant of the pool
+    /// 0: constant product
+    /// 1: stable swap
+    /// 2: weighted
+    /// 3: offset
+    pub curve_type: u8,
+    pub padding1: [u8; 5],
     pub creator_fees_token_0: u64,
     pub creator_fees_token_1: u64,
+    /// Amplification coefficient of a stable swap pool at `start_ramp_ts`
+    pub initial_amp: u64,
+    /// Amplification coefficient of a stable swap pool from `stop_ramp_ts` on
+    pub target_amp: u64,
+    /// The timestamp the amplification coefficient ramp starts
+    pub start_ramp_ts: u64,
+    /// The timestamp the amplification coefficient ramp stops
+    pub stop_ramp_ts: u64,
+    /// Reduced token_0 weight of a weighted pool
+    pub token_0_weight: u64,
+    /// Reduced token_1 weight of a weighted pool
+    pub token_1_weight: u64,
+    /// Virtual token_1 reserves of an offset pool, added to the real balance
+    /// when pricing swaps, set at initialize and never changed
+    pub token_1_offset: u64,
+    /// The maximum deviation of the token_0 price after a swap from its time weighted
+    /// average, in bps, zero when swaps are not checked
+    pub max_price_deviation_bps: u64,
+    /// The window of the time weighted average price swaps are checked against, in seconds,
+    /// at most the history duration of the oracle
+    pub price_deviation_window: u64,
+    /// Bitwise representation of the fee rates the pool overrides
+    /// bit0, 1: `trade_fee_rate_override` replaces the config trade fee rate
+    /// bit1, 1: `protocol_fee_rate_override` replaces the config protocol fee rate
+    /// bit2, 1: `fund_fee_rate_override` replaces the config fund fee rate
+    pub fee_override_flags: u64,
+    /// The trade fee of the pool, denominated in hundredths of a bip (10^-6)
+    pub trade_fee_rate_override: u64,
+    /// The protocol fee of the pool
+    pub protocol_fee_rate_override: u64,
+    /// The fund fee of the pool, denominated in hundredths of a bip (10^-6)
+    pub fund_fee_rate_override: u64,
     /// padding for future updates
-    pub padding: [u64; 28],
+    pub padding: [u64; 15],
 }
 
 impl PoolState {
-    pub const LEN: usize = 8 + 10 * 32 + 1 * 5 + 8 * 7 + 1 * 2 + 6 * 1 + 2 * 8 + 8 * 28;
+    pub const LEN: usize = 8
+        + 10 * 32
+        + 1 * 5
+        + 8 * 7
+        + 1 * 3
+        + 5 * 1
+        + 2 * 8
+        + 4 * 8
+        + 3 * 8
+        + 2 * 8
+        + 4 * 8
+        + 8 * 15;
 
     pub fn initialize(
         &mut self,
-        auth_bump: u8,
+        accounts: PoolAccounts,
         lp_supply: u64,
         open_time: u64,
-        pool_creator: Pubkey,
-        amm_config: Pubkey,
-        token_0_vault: Pubkey,
-        token_1_vault: Pubkey,
-        token_0_mint: &InterfaceAccount<Mint>,
-        token_1_mint: &InterfaceAccount<Mint>,
-        lp_mint: Pubkey,
-        lp_mint_decimals: u8,
-        observation_key: Pubkey,
         creator_fee_on: CreatorFeeOn,
         enable_creator_fee: bool,
+        curve_params: CurveParams,
     ) {
-        self.amm_config = amm_config.key();
-        self.pool_creator = pool_creator.key();
-        self.token_0_vault = token_0_vault;
-        self.token_1_vault = token_1_vault;
-        self.lp_mint = lp_mint.key();
-        self.token_0_mint = token_0_mint.key();
-        self.token_1_mint = token_1_mint.key();
-        self.token_0_program = *token_0_mint.to_account_info().owner;
-        self.token_1_program = *token_1_mint.to_account_info().owner;
-        self.observation_key = observation_key;
-        self.auth_bump = auth_bump;
-        self.lp_mint_decimals = lp_mint_decimals;
-        self.mint_0_decimals = token_0_mint.decimals;
-        self.mint_1_decimals = token_1_mint.decimals;
+        self.amm_config = accounts.amm_config;
+        self.pool_creator = accounts.pool_creator;
+        self.token_0_vault = accounts.token_0_vault;
+        self.token_1_vault = accounts.token_1_vault;
+        self.lp_mint = accounts.lp_mint.key();
+        self.token_0_mint = accounts.token_0_mint.key();
+        self.token_1_mint = accounts.token_1_mint.key();
+        self.token_0_program = *accounts.token_0_mint.to_account_info().owner;
+        self.token_1_program = *accounts.token_1_mint.to_account_info().owner;
+        self.observation_key = accounts.observation_key;
+        self.auth_bump = accounts.auth_bump;
+        self.lp_mint_decimals = accounts.lp_mint.decimals;
+        self.mint_0_decimals = accounts.token_0_mint.decimals;
+        self.mint_1_decimals = accounts.token_1_mint.decimals;
         self.lp_supply = lp_supply;
         self.protocol_fees_token_0 = 0;
         self.protocol_fees_token_1 = 0;
@@ -171,10 +254,35 @@ impl PoolState {
         self.recent_epoch = Clock::get().unwrap().epoch;
         self.creator_fee_on = creator_fee_on.to_u8();
         self.enable_creator_fee = enable_creator_fee;
-        self.padding1 = [0u8; 6];
+        self.curve_type = curve_params.curve_type().to_u8();
+        self.padding1 = [0u8; 5];
         self.creator_fees_token_0 = 0;
         self.creator_fees_token_1 = 0;
-        self.padding = [0u64; 28];
+        let amp = match curve_params {
+            CurveParams::StableSwap { amp } => amp,
+            _ => 0,
+        };
+        self.initial_amp = amp;
+        self.target_amp = amp;
+        self.start_ramp_ts = 0;
+        self.stop_ramp_ts = 0;
+        let (token_0_weight, token_1_weight) = match curve_params {
+            CurveParams::Weighted {
+                token_0_weight,
+                token_1_weight,
+            } => reduce_weights(token_0_weight, token_1_weight),
+            _ => (0, 0),
+        };
+        self.token_0_weight = token_0_weight;
+        self.token_1_weight = token_1_weight;
+        self.token_1_offset = curve_params.token_1_offset();
+        self.max_price_deviation_bps = 0;
+        self.price_deviation_window = 0;
+        self.fee_override_flags = 0;
+        self.trade_fee_rate_override = 0;
+        self.protocol_fee_rate_override = 0;
+        self.fund_fee_rate_override = 0;
+        self.padding = [0u64; 15];
     }
 
     pub fn set_status(&mut self, status: u8) {
@@ -197,6 +305,21 @@ impl PoolState {
         self.status.bitand(status) == 0
     }
 
+    /// Get status by bit of the pool, its amm config and the global config,
+    /// return true if none of them disables it
+    pub fn get_status_by_bit_with_configs(
+        &self,
+        bit: PoolStatusBitIndex,
+        amm_config: &AmmConfig,
+        global_config: &GlobalConfig,
+    ) -> bool {
+        self.get_status_by_bit(bit)
+            && amm_config.get_status_by_bit(bit)
+            && global_config.get_status_by_bit(bit)
+    }
+
+    /// Vault balances available to the curve, which excludes the fees owed and
+    /// includes the virtual token_1 reserves of an offset pool
     pub fn vault_amount_without_fee(&self, vault_0: u64, vault_1: u64) -> Result<(u64, u64)> {
         let fees_token_0 = self
             .protocol_fees_token_0
@@ -216,18 +339,142 @@ impl PoolState {
                 .ok_or(ErrorCode::InsufficientVault)?,
             vault_1
                 .checked_sub(fees_token_1)
-                .ok_or(ErrorCode::InsufficientVault)?,
+                .ok_or(ErrorCode::InsufficientVault)?
+                .checked_add(self.token_1_offset)
+                .ok_or(ErrorCode::MathOverflow)?,
         ))
     }
 
-    pub fn token_price_x32(&self, vault_0: u64, vault_1: u64) -> Result<(u128, u128)> {
+    /// Build the curve used to price swaps and liquidity changes of the pool
+    pub fn curve(&self, block_timestamp: u64) -> Result<Box<dyn Curve>> {
+        Ok(match CurveType::from_u8(self.curve_type)? {
+            CurveType::ConstantProduct => Box::new(ConstantProductCurve),
+            CurveType::StableSwap => {
+                Box::new(StableSwapCurve::new(self.current_amp(block_timestamp)))
+            }
+            CurveType::Weighted => {
+                Box::new(WeightedCurve::new(self.token_0_weight, self.token_1_weight))
+            }
+            CurveType::Offset => Box::new(OffsetCurve::new(self.token_1_offset)),
+        })
+    }
+
+    /// Amplification coefficient of a stable swap pool, linearly ramped from
+    /// `initial_amp` to `target_amp` between `start_ramp_ts` and `stop_ramp_ts`
+    pub fn current_amp(&self, block_timestamp: u64) -> u64 {
+        if block_timestamp >= self.stop_ramp_ts || self.stop_ramp_ts <= self.start_ramp_ts {
+            return self.target_amp;
+        }
+        let elapsed = u128::from(block_timestamp.saturating_sub(self.start_ramp_ts));
+        let duration = u128::from(self.stop_ramp_ts - self.start_ramp_ts);
+        let initial_amp = u128::from(self.initial_amp);
+        let target_amp = u128::from(self.target_amp);
+        let amp = if target_amp > initial_amp {
+            initial_amp + (target_amp - initial_amp) * elapsed / duration
+        } else {
+            initial_amp - (initial_amp - target_amp) * elapsed / duration
+        };
+        amp as u64
+    }
+
+    /// Spot prices of the pool, the marginal price of each token in the other on the
+    /// curve of the pool
+    pub fn token_price_x32(
+        &self,
+        vault_0: u64,
+        vault_1: u64,
+        block_timestamp: u64,
+    ) -> Result<(u128, u128)> {
         let (token_0_amount, token_1_amount) = self.vault_amount_without_fee(vault_0, vault_1)?;
+        let (token_0_amount, token_1_amount) =
+            (u128::from(token_0_amount), u128::from(token_1_amount));
+        let curve = self.curve(block_timestamp)?;
         Ok((
-            token_1_amount as u128 * Q32 as u128 / token_0_amount as u128,
-            token_0_amount as u128 * Q32 as u128 / token_1_amount as u128,
+            curve
+                .marginal_price_x32(token_0_amount, token_1_amount, TradeDirection::ZeroForOne)
+                .ok_or(ErrorCode::MathOverflow)?,
+            curve
+                .marginal_price_x32(token_1_amount, token_0_amount, TradeDirection::OneForZero)
+                .ok_or(ErrorCode::MathOverflow)?,
         ))
     }
 
+    /// Rejects a swap that leaves the token_0 spot price more than `max_price_deviation_bps`
+    /// away from its time weighted average over `price_deviation_window`. The window is
+    /// cut to the recorded history of the oracle, and until the oracle has recorded any
+    /// history the price before the swap is the reference.
+    ///
+    /// # Arguments
+    ///
+    /// * `observation_state` - The oracle observations of the pool
+    /// * `block_timestamp` - The current timestamp
+    /// * `token_0_price_x32` - The token_0 price before the swap
+    /// * `token_1_price_x32` - The token_1 price before the swap
+    /// * `new_token_0_amount` - The token_0 amount available to the curve after the swap
+    /// * `new_token_1_amount` - The token_1 amount available to the curve after the swap
+    ///
+    pub fn check_price_deviation<S, E>(
+        &self,
+        observation_state: &ObservationRing<S, E>,
+        block_timestamp: u64,
+        token_0_price_x32: u128,
+        token_1_price_x32: u128,
+        new_token_0_amount: u128,
+        new_token_1_amount: u128,
+    ) -> Result<()>
+    where
+        S: Deref<Target = ObservationState>,
+        E: Deref<Target = [Observation]>,
+    {
+        if self.max_price_deviation_bps == 0 {
+            return Ok(());
+        }
+        let window = match observation_state.oldest_observation_timestamp() {
+            Some(oldest_timestamp) => self
+                .price_deviation_window
+                .min(block_timestamp.saturating_sub(oldest_timestamp)),
+            None => 0,
+        };
+        // The current prices extend the cumulative prices up to the swap
+        let average_price_x32 = if window == 0 {
+            token_0_price_x32
+        } else {
+            observation_state
+                .consult_twap(
+                    block_timestamp,
+                    window,
+                    token_0_price_x32,
+                    token_1_price_x32,
+                )?
+                .0
+        };
+        if average_price_x32 == 0 {
+            return Ok(());
+        }
+        // Priced on the curve of the pool, the same way as the oracle prices
+        let new_price_x32 = U256::from(
+            self.curve(block_timestamp)?
+                .marginal_price_x32(
+                    new_token_0_amount,
+                    new_token_1_amount,
+                    TradeDirection::ZeroForOne,
+                )
+                .ok_or(ErrorCode::MathOverflow)?,
+        );
+        let average_price_x32 = U256::from(average_price_x32);
+        let deviation = if new_price_x32 > average_price_x32 {
+            new_price_x32 - average_price_x32
+        } else {
+            average_price_x32 - new_price_x32
+        };
+        let deviation_bps = deviation * U256::from(BPS_DENOMINATOR) / average_price_x32;
+        require!(
+            deviation_bps <= U256::from(self.max_price_deviation_bps),
+            ErrorCode::PriceDeviationExceeded
+        );
+        Ok(())
+    }
+
     pub fn update_lp_supply(
         &mut self,
         liquidity_delta: u64,
@@ -266,6 +513,7 @@ impl PoolState {
         output_vault_key: Pubkey,
         input_vault_amount: u64,
         output_vault_amount: u64,
+        block_timestamp: u64,
     ) -> Result<SwapParams> {
         let (
             trade_direction,
@@ -278,7 +526,7 @@ impl PoolState {
             let (total_input_token_amount, total_output_token_amount) =
                 self.vault_amount_without_fee(input_vault_amount, output_vault_amount)?;
             let (token_0_price_x64, token_1_price_x64) =
-                self.token_price_x32(input_vault_amount, output_vault_amount)?;
+                self.token_price_x32(input_vault_amount, output_vault_amount, block_timestamp)?;
 
             (
                 TradeDirection::ZeroForOne,
@@ -292,7 +540,7 @@ impl PoolState {
             let (total_output_token_amount, total_input_token_amount) =
                 self.vault_amount_without_fee(output_vault_amount, input_vault_amount)?;
             let (token_0_price_x64, token_1_price_x64) =
-                self.token_price_x32(output_vault_amount, input_vault_amount)?;
+                self.token_price_x32(output_vault_amount, input_vault_amount, block_timestamp)?;
 
             (
                 TradeDirection::OneForZero,
@@ -315,6 +563,122 @@ impl PoolState {
         })
     }
 
+    pub fn set_fee_override(&mut self, bit: PoolFeeOverrideBitIndex, fee_rate: Option<u64>) {
+        if let Some(fee_rate) = fee_rate {
+            match bit {
+                PoolFeeOverrideBitIndex::TradeFeeRate => self.trade_fee_rate_override = fee_rate,
+                PoolFeeOverrideBitIndex::ProtocolFeeRate => {
+                    self.protocol_fee_rate_override = fee_rate
+                }
+                PoolFeeOverrideBitIndex::FundFeeRate => self.fund_fee_rate_override = fee_rate,
+            }
+        }
+        let s = 1u64 << (bit as u8);
+        if fee_rate.is_some() {
+            self.fee_override_flags = self.fee_override_flags.bitor(s);
+        } else {
+            self.fee_override_flags = self.fee_override_flags.bitand(!s);
+        }
+    }
+
+    /// Return true if the fee rate is overridden by the pool
+    pub fn get_fee_override_by_bit(&self, bit: PoolFeeOverrideBitIndex) -> bool {
+        let fee_override_flags = 1u64 << (bit as u8);
+        self.fee_override_flags.bitand(fee_override_flags) != 0
+    }
+
+    /// The current override of the fee rate `update` changes
+    pub fn current_fee_override(&self, update: PoolFeeRateUpdate) -> PoolFeeRateUpdate {
+        match update {
+            PoolFeeRateUpdate::TradeFeeRate(_) => PoolFeeRateUpdate::TradeFeeRate(
+                self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::TradeFeeRate)
+                    .then_some(self.trade_fee_rate_override),
+            ),
+            PoolFeeRateUpdate::ProtocolFeeRate(_) => PoolFeeRateUpdate::ProtocolFeeRate(
+                self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::ProtocolFeeRate)
+                    .then_some(self.protocol_fee_rate_override),
+            ),
+            PoolFeeRateUpdate::FundFeeRate(_) => PoolFeeRateUpdate::FundFeeRate(
+                self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::FundFeeRate)
+                    .then_some(self.fund_fee_rate_override),
+            ),
+        }
+    }
+
+    /// The trade fee rate of the pool, before any dynamic fee is added
+    pub fn trade_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::TradeFeeRate) {
+            self.trade_fee_rate_override
+        } else {
+            amm_config.trade_fee_rate
+        }
+    }
+
+    pub fn protocol_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::ProtocolFeeRate) {
+            self.protocol_fee_rate_override
+        } else {
+            amm_config.protocol_fee_rate
+        }
+    }
+
+    pub fn fund_fee_rate(&self, amm_config: &AmmConfig) -> u64 {
+        if self.get_fee_override_by_bit(PoolFeeOverrideBitIndex::FundFeeRate) {
+            self.fund_fee_rate_override
+        } else {
+            amm_config.fund_fee_rate
+        }
+    }
+
+    /// Check the fee rates of the pool under `amm_config` hold the same invariants
+    /// amm config updates keep for the config itself
+    pub fn check_fee_rates(&self, amm_config: &AmmConfig) -> Result<()> {
+        let trade_fee_rate = self.trade_fee_rate(amm_config);
+        let protocol_fee_rate = self.protocol_fee_rate(amm_config);
+        let fund_fee_rate = self.fund_fee_rate(amm_config);
+        let below_denominator = |a: u64, b: u64| {
+            a.checked_add(b)
+                .is_some_and(|rate| rate < FEE_RATE_DENOMINATOR_VALUE)
+        };
+        require!(
+            below_denominator(trade_fee_rate, amm_config.creator_fee_rate)
+                && below_denominator(amm_config.max_trade_fee_rate, amm_config.creator_fee_rate)
+                && protocol_fee_rate
+                    .checked_add(fund_fee_rate)
+                    .is_some_and(|rate| rate <= FEE_RATE_DENOMINATOR_VALUE),
+            ErrorCode::InvalidFeeRate
+        );
+        Ok(())
+    }
+
+    /// The fee rates of a swap against the pool, `trade_fee_rate` is the rate the swap pays,
+    /// which already includes any dynamic fee.
+    ///
+    /// The overrides are only checked when they are set, so a later amm config update can
+    /// leave them out of bounds; the rates are checked again here and the swap fails if they are.
+    pub fn fee_rates(
+        &self,
+        amm_config: &AmmConfig,
+        trade_fee_rate: u64,
+        is_creator_fee_on_input: bool,
+    ) -> Result<FeeRates> {
+        self.check_fee_rates(amm_config)?;
+        let creator_fee_rate = self.adjust_creator_fee_rate(amm_config.creator_fee_rate);
+        require!(
+            trade_fee_rate
+                .checked_add(creator_fee_rate)
+                .is_some_and(|rate| rate < FEE_RATE_DENOMINATOR_VALUE),
+            ErrorCode::InvalidFeeRate
+        );
+        Ok(FeeRates {
+            trade_fee_rate,
+            creator_fee_rate,
+            protocol_fee_rate: self.protocol_fee_rate(amm_config),
+            fund_fee_rate: self.fund_fee_rate(amm_config),
+            is_creator_fee_on_input,
+        })
+    }
+
     pub fn adjust_creator_fee_rate(&self, creator_fee_rate: u64) -> u64 {
         if self.enable_creator_fee {
             creator_fee_rate
@@ -378,9 +742,254 @@ pub mod pool_test {
         assert_eq!(std::mem::size_of::<PoolState>(), PoolState::LEN - 8)
     }
 
+    #[test]
+    fn fee_override_test() {
+        let mut pool_state = PoolState::default();
+        let amm_config = AmmConfig {
+            trade_fee_rate: 2500,
+            protocol_fee_rate: 120000,
+            fund_fee_rate: 40000,
+            ..Default::default()
+        };
+        assert_eq!(pool_state.trade_fee_rate(&amm_config), 2500);
+
+        // a zero override is still an override
+        pool_state.set_fee_override(PoolFeeOverrideBitIndex::TradeFeeRate, Some(0));
+        pool_state.set_fee_override(PoolFeeOverrideBitIndex::FundFeeRate, Some(10000));
+        assert_eq!(pool_state.trade_fee_rate(&amm_config), 0);
+        assert_eq!(pool_state.protocol_fee_rate(&amm_config), 120000);
+        assert_eq!(pool_state.fund_fee_rate(&amm_config), 10000);
+
+        assert_eq!(
+            pool_state.current_fee_override(PoolFeeRateUpdate::TradeFeeRate(None)),
+            PoolFeeRateUpdate::TradeFeeRate(Some(0))
+        );
+
+        pool_state.set_fee_override(PoolFeeOverrideBitIndex::TradeFeeRate, None);
+        assert_eq!(pool_state.trade_fee_rate(&amm_config), 2500);
+        assert_eq!(
+            pool_state.current_fee_override(PoolFeeRateUpdate::TradeFeeRate(Some(100))),
+            PoolFeeRateUpdate::TradeFeeRate(None)
+        );
+        assert_eq!(pool_state.fund_fee_rate(&amm_config), 10000);
+        assert!(pool_state.check_fee_rates(&amm_config).is_ok());
+
+        // the fund fee override no longer fits next to the protocol fee of the config
+        let amm_config = AmmConfig {
+            protocol_fee_rate: 955000,
+            ..amm_config
+        };
+        pool_state.set_fee_override(PoolFeeOverrideBitIndex::FundFeeRate, Some(50000));
+        assert!(pool_state.check_fee_rates(&amm_config).is_err());
+        // swaps fail closed instead of using the out of bounds rates
+        assert!(pool_state.fee_rates(&amm_config, 2500, true).is_err());
+        pool_state.set_fee_override(PoolFeeOverrideBitIndex::FundFeeRate, None);
+        assert!(pool_state.check_fee_rates(&amm_config).is_ok());
+        assert!(pool_state.fee_rates(&amm_config, 2500, true).is_ok());
+    }
+
+    #[test]
+    fn pool_curve_type_test() {
+        let mut pool_state = PoolState::default();
+        // existing pools have a zeroed curve_type and keep the constant product curve
+        assert_eq!(
+            CurveType::from_u8(pool_state.curve_type).unwrap(),
+            CurveType::ConstantProduct
+        );
+        assert!(pool_state.curve(0).is_ok());
+
+        pool_state.curve_type = u8::MAX;
+        assert!(pool_state.curve(0).is_err());
+    }
+
+    #[test]
+    fn offset_vault_amount_test() {
+        let pool_state = PoolState {
+            curve_type: CurveType::Offset.to_u8(),
+            token_1_offset: 1_000_000,
+            protocol_fees_token_1: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            pool_state.vault_amount_without_fee(1_000_000, 10).unwrap(),
+            (1_000_000, 1_000_000)
+        );
+        assert_eq!(
+            pool_state.token_price_x32(1_000_000, 10, 0).unwrap(),
+            (Q32, Q32)
+        );
+    }
+
+    #[test]
+    fn weighted_token_price_test() {
+        let mut pool_state = PoolState::default();
+        // 400 token_0 against 100 token_1 is a 1:1 price in an 80/20 pool
+        assert_eq!(
+            pool_state.token_price_x32(400, 100, 0).unwrap(),
+            (Q32 / 4, Q32 * 4)
+        );
+        pool_state.curve_type = CurveType::Weighted.to_u8();
+        pool_state.token_0_weight = 4;
+        pool_state.token_1_weight = 1;
+        assert_eq!(pool_state.token_price_x32(400, 100, 0).unwrap(), (Q32, Q32));
+    }
+
+    #[test]
+    fn stable_token_price_test() {
+        let pool_state = PoolState {
+            curve_type: CurveType::StableSwap.to_u8(),
+            initial_amp: 100,
+            target_amp: 100,
+            ..Default::default()
+        };
+        // the reserve ratio is 2:1, but a stable pool still trades close to 1:1
+        let (token_0_price_x32, token_1_price_x32) =
+            pool_state.token_price_x32(2_000_000, 1_000_000, 0).unwrap();
+        assert!(token_0_price_x32 < Q32 && token_0_price_x32 > Q32 * 98 / 100);
+        assert!(token_1_price_x32 > Q32 && token_1_price_x32 < Q32 * 102 / 100);
+    }
+
+    #[test]
+    fn current_amp_ramp_test() {
+        let mut pool_state = PoolState {
+            curve_type: CurveType::StableSwap.to_u8(),
+            initial_amp: 100,
+            target_amp: 100,
+            ..Default::default()
+        };
+        assert_eq!(pool_state.current_amp(1_000), 100);
+
+        // ramp up
+        pool_state.target_amp = 1_000;
+        pool_state.start_ramp_ts = 1_000;
+        pool_state.stop_ramp_ts = 1_000 + 86400;
+        assert_eq!(pool_state.current_amp(1_000), 100);
+        assert_eq!(pool_state.current_amp(1_000 + 43200), 550);
+        assert_eq!(pool_state.current_amp(1_000 + 86400), 1_000);
+        assert_eq!(pool_state.current_amp(u64::MAX), 1_000);
+
+        // ramp down
+        pool_state.initial_amp = 1_000;
+        pool_state.target_amp = 100;
+        assert_eq!(pool_state.current_amp(1_000 + 43200), 550);
+        assert_eq!(pool_state.current_amp(1_000 + 86400), 100);
+    }
+
+    #[test]
+    fn price_deviation_test() {
+        let mut pool_state = PoolState::default();
+        let mut state = ObservationState::default();
+        let mut observation_state = ObservationRing {
+            state: &mut state,
+            extension: &mut [][..],
+        };
+        pool_state.max_price_deviation_bps = 500;
+        pool_state.price_deviation_window = 300;
+        // nothing recorded yet, the price before the swap is the reference
+        assert!(pool_state
+            .check_price_deviation(&observation_state, 1_000, Q32, Q32, 1_000, 1_050)
+            .is_ok());
+        assert!(pool_state
+            .check_price_deviation(&observation_state, 1_000, Q32, Q32, 1_000, 2_000)
+            .is_err());
+        for i in 0..=20 {
+            observation_state.update(1_000 + i * 15, Q32, Q32).unwrap();
+        }
+        let block_timestamp = 1_000 + 20 * 15;
+        assert!(pool_state
+            .check_price_deviation(&observation_state, block_timestamp, Q32, Q32, 1_000, 1_050)
+            .is_ok());
+        assert!(pool_state
+            .check_price_deviation(&observation_state, block_timestamp, Q32, Q32, 1_050, 1_000)
+            .is_ok());
+        assert!(pool_state
+            .check_price_deviation(&observation_state, block_timestamp, Q32, Q32, 1_000, 1_051)
+            .is_err());
+        assert!(pool_state
+            .check_price_deviation(&observation_state, block_timestamp, Q32, Q32, 1_000, 940)
+            .is_err());
+        // disabled
+        pool_state.max_price_deviation_bps = 0;
+        assert!(pool_state
+            .check_price_deviation(&observation_state, block_timestamp, Q32, Q32, 1_000, 2_000)
+            .is_ok());
+    }
+
+    #[test]
+    fn stable_price_deviation_test() {
+        let pool_state = PoolState {
+            curve_type: CurveType::StableSwap.to_u8(),
+            initial_amp: 100,
+            target_amp: 100,
+            max_price_deviation_bps: 100,
+            ..Default::default()
+        };
+        let mut state = ObservationState::default();
+        let observation_state = ObservationRing {
+            state: &mut state,
+            extension: &mut [][..],
+        };
+        let (token_0_price_x32, token_1_price_x32) =
+            pool_state.token_price_x32(2_000_000, 1_000_000, 0).unwrap();
+        // the reserve ratio halves, but a stable pool still trades close to 1:1
+        assert!(pool_state
+            .check_price_deviation(
+                &observation_state,
+                0,
+                token_0_price_x32,
+                token_1_price_x32,
+                2_010_000,
+                990_000,
+            )
+            .is_ok());
+        // draining token_1 does move the price
+        assert!(pool_state
+            .check_price_deviation(
+                &observation_state,
+                0,
+                token_0_price_x32,
+                token_1_price_x32,
+                2_900_000,
+                100_000,
+            )
+            .is_err());
+    }
+
     mod pool_status_test {
         use super::*;
 
+        #[test]
+        fn get_status_by_bit_with_configs() {
+            let pool_state = PoolState::default();
+            let mut amm_config = AmmConfig::default();
+            let mut global_config = GlobalConfig::default();
+            assert!(pool_state.get_status_by_bit_with_configs(
+                PoolStatusBitIndex::Swap,
+                &amm_config,
+                &global_config
+            ));
+
+            amm_config.status = 4;
+            assert!(!pool_state.get_status_by_bit_with_configs(
+                PoolStatusBitIndex::Swap,
+                &amm_config,
+                &global_config
+            ));
+            assert!(pool_state.get_status_by_bit_with_configs(
+                PoolStatusBitIndex::Deposit,
+                &amm_config,
+                &global_config
+            ));
+
+            amm_config.status = 0;
+            global_config.status = 1;
+            assert!(!pool_state.get_status_by_bit_with_configs(
+                PoolStatusBitIndex::Deposit,
+                &amm_config,
+                &global_config
+            ));
+        }
+
         #[test]
         fn get_set_status_by_bit() {
             let mut pool_state = PoolState::default();
@@ -1,4 +1,4 @@
-///! 128 and 256 bit numbers
+///! 128, 256 and 512 bit numbers
 ///! U128 is more efficient that u128
 ///! https://github.com/solana-labs/solana/issues/19549
 use uint::construct_uint;
@@ -10,6 +10,10 @@ construct_uint! {
     pub struct U256(4);
 }
 
+construct_uint! {
+    pub struct U512(8);
+}
+
 pub trait CheckedCeilDiv: Sized {
     /// Perform ceiling division
     fn checked_ceil_div(&self, rhs: Self) -> Option<Self>;
@@ -4,12 +4,17 @@ import { RaydiumCpSwap } from "../target/types/raydium_cp_swap";
 import {
   calculateFee,
   calculatePreFeeAmount,
+  createAmmConfig,
+  createTokenMintAndAssociatedTokenAccount,
   deposit,
+  depositSingleToken,
   getUserAndPoolVaultAmount,
+  initialize,
   setupDepositTest,
 } from "./utils";
 import { assert } from "chai";
 import { MAX_FEE_BASIS_POINTS, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair } from "@solana/web3.js";
 
 describe("deposit test", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
@@ -334,4 +339,71 @@ describe("deposit test", () => {
       );
     }
   });
+
+  it("deposit single token into stable swap and weighted pools within the compute budget", async () => {
+    const connection = anchor.getProvider().connection;
+    const configAddress = await createAmmConfig(
+      program,
+      connection,
+      owner,
+      0,
+      new BN(10),
+      new BN(1000),
+      new BN(25000),
+      new BN(0),
+      confirmOptions
+    );
+    for (const curveParams of [
+      { stableSwap: { amp: new BN(100) } },
+      { weighted: { token0Weight: new BN(80), token1Weight: new BN(20) } },
+    ]) {
+      const [{ token0, token0Program }, { token1, token1Program }] =
+        await createTokenMintAndAssociatedTokenAccount(
+          connection,
+          owner,
+          new Keypair(),
+          { transferFeeBasisPoints: 0, MaxFee: 0 }
+        );
+      const { poolAddress, poolState } = await initialize(
+        program,
+        owner,
+        configAddress,
+        token0,
+        token0Program,
+        token1,
+        token1Program,
+        confirmOptions,
+        undefined,
+        undefined,
+        curveParams
+      );
+
+      const tx = await depositSingleToken(
+        program,
+        owner,
+        poolAddress,
+        token0,
+        token0Program,
+        token1,
+        new BN(1000000000),
+        new BN(0),
+        { skipPreflight: true, commitment: "confirmed" }
+      );
+      const transaction = await connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      assert.isNull(transaction.meta.err);
+      console.log(
+        "deposit single token",
+        JSON.stringify(curveParams),
+        "compute units:",
+        transaction.meta.computeUnitsConsumed
+      );
+      assert.isBelow(transaction.meta.computeUnitsConsumed, 1_400_000);
+
+      const newPoolState = await program.account.poolState.fetch(poolAddress);
+      assert(newPoolState.lpSupply.gt(poolState.lpSupply));
+    }
+  });
 });
@@ -18,6 +18,7 @@ import {
 import {
   accountExist,
   sendTransaction,
+  getGlobalConfigAddress,
   getAmmConfigAddress,
   getAuthAddress,
   getPoolAddress,
@@ -216,6 +217,31 @@ export async function setupSwapTest(
   return { configAddress, poolAddress, poolState };
 }
 
+export async function createGlobalConfig(
+  program: Program<RaydiumCpSwap>,
+  connection: Connection,
+  owner: Signer,
+  confirmOptions?: ConfirmOptions
+): Promise<PublicKey> {
+  const [address, _] = await getGlobalConfigAddress(program.programId);
+  if (await accountExist(connection, address)) {
+    return address;
+  }
+
+  const ix = await program.methods
+    .createGlobalConfig()
+    .accounts({
+      owner: owner.publicKey,
+      globalConfig: address,
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+
+  const tx = await sendTransaction(connection, [ix], [owner], confirmOptions);
+  console.log("init global config tx: ", tx);
+  return address;
+}
+
 export async function createAmmConfig(
   program: Program<RaydiumCpSwap>,
   connection: Connection,
@@ -234,6 +260,12 @@ export async function createAmmConfig(
   if (await accountExist(connection, address)) {
     return address;
   }
+  const globalConfig = await createGlobalConfig(
+    program,
+    connection,
+    owner,
+    confirmOptions
+  );
 
   const ix = await program.methods
     .createAmmConfig(
@@ -245,6 +277,7 @@ export async function createAmmConfig(
     )
     .accounts({
       owner: owner.publicKey,
+      globalConfig,
       ammConfig: address,
       systemProgram: SystemProgram.programId,
     })
@@ -268,7 +301,8 @@ export async function initialize(
     initAmount0: new BN(10000000000),
     initAmount1: new BN(20000000000),
   },
-  createPoolFee = new PublicKey("DNXgeM9EiiaAbaWvwjHj9fQQLAX5ZsfHyvmYUNRAdNC8")
+  createPoolFee = new PublicKey("DNXgeM9EiiaAbaWvwjHj9fQQLAX5ZsfHyvmYUNRAdNC8"),
+  curveParams?: any
 ) {
   const [auth] = await getAuthAddress(program.programId);
   const [poolAddress] = await getPoolAddress(
@@ -317,30 +351,43 @@ export async function initialize(
     false,
     token1Program
   );
-  await program.methods
-    .initialize(initAmount.initAmount0, initAmount.initAmount1, new BN(0))
-    .accounts({
-      creator: creator.publicKey,
-      ammConfig: configAddress,
-      authority: auth,
-      poolState: poolAddress,
-      token0Mint: token0,
-      token1Mint: token1,
-      lpMint: lpMintAddress,
-      creatorToken0,
-      creatorToken1,
-      creatorLpToken: creatorLpTokenAddress,
-      token0Vault: vault0,
-      token1Vault: vault1,
-      createPoolFee,
-      observationState: observationAddress,
-      tokenProgram: TOKEN_PROGRAM_ID,
-      token0Program: token0Program,
-      token1Program: token1Program,
-      systemProgram: SystemProgram.programId,
-      rent: SYSVAR_RENT_PUBKEY,
-    })
-    .rpc(confirmOptions);
+  const accounts = {
+    creator: creator.publicKey,
+    ammConfig: configAddress,
+    authority: auth,
+    poolState: poolAddress,
+    token0Mint: token0,
+    token1Mint: token1,
+    lpMint: lpMintAddress,
+    creatorToken0,
+    creatorToken1,
+    creatorLpToken: creatorLpTokenAddress,
+    token0Vault: vault0,
+    token1Vault: vault1,
+    createPoolFee,
+    observationState: observationAddress,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    token0Program: token0Program,
+    token1Program: token1Program,
+    systemProgram: SystemProgram.programId,
+    rent: SYSVAR_RENT_PUBKEY,
+  };
+  if (curveParams == undefined) {
+    await program.methods
+      .initialize(initAmount.initAmount0, initAmount.initAmount1, new BN(0))
+      .accounts(accounts)
+      .rpc(confirmOptions);
+  } else {
+    await program.methods
+      .initializeWithCurve(
+        initAmount.initAmount0,
+        initAmount.initAmount1,
+        new BN(0),
+        curveParams
+      )
+      .accounts(accounts)
+      .rpc(confirmOptions);
+  }
   const poolState = await program.account.poolState.fetch(poolAddress);
   return { poolAddress, poolState };
 }
@@ -402,6 +449,13 @@ export async function deposit(
     token1Program
   );
 
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+
+  const [globalConfig] = await getGlobalConfigAddress(program.programId);
+
   const tx = await program.methods
     .deposit(lp_token_amount, maximum_token_0_amount, maximum_token_1_amount)
     .accounts({
@@ -418,7 +472,82 @@ export async function deposit(
       vault0Mint: token0,
       vault1Mint: token1,
       lpMint: lpMintAddress,
+      observationState: observationAddress,
+      ammConfig: configAddress,
+      globalConfig,
+    })
+    .rpc(confirmOptions);
+  return tx;
+}
+
+export async function depositSingleToken(
+  program: Program<RaydiumCpSwap>,
+  owner: Signer,
+  poolAddress: PublicKey,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  outputToken: PublicKey,
+  amount_in: BN,
+  minimum_lp_out: BN,
+  confirmOptions?: ConfirmOptions
+) {
+  const [auth] = await getAuthAddress(program.programId);
+  const [lpMintAddress] = await getPoolLpMintAddress(
+    poolAddress,
+    program.programId
+  );
+  const [inputVault] = await getPoolVaultAddress(
+    poolAddress,
+    inputToken,
+    program.programId
+  );
+  const [outputVault] = await getPoolVaultAddress(
+    poolAddress,
+    outputToken,
+    program.programId
+  );
+  const [ownerLpToken] = await PublicKey.findProgramAddress(
+    [
+      owner.publicKey.toBuffer(),
+      TOKEN_PROGRAM_ID.toBuffer(),
+      lpMintAddress.toBuffer(),
+    ],
+    ASSOCIATED_PROGRAM_ID
+  );
+  const inputTokenAccount = getAssociatedTokenAddressSync(
+    inputToken,
+    owner.publicKey,
+    false,
+    inputTokenProgram
+  );
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+  const [globalConfig] = await getGlobalConfigAddress(program.programId);
+  const poolState = await program.account.poolState.fetch(poolAddress);
+
+  const tx = await program.methods
+    .depositSingleToken(amount_in, minimum_lp_out)
+    .accounts({
+      owner: owner.publicKey,
+      authority: auth,
+      ammConfig: poolState.ammConfig,
+      poolState: poolAddress,
+      ownerLpToken,
+      inputTokenAccount,
+      inputVault,
+      outputVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      inputTokenProgram,
+      inputTokenMint: inputToken,
+      lpMint: lpMintAddress,
+      observationState: observationAddress,
+      globalConfig,
     })
+    .preInstructions([
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+    ])
     .rpc(confirmOptions);
   return tx;
 }
@@ -480,6 +609,13 @@ export async function withdraw(
     token1Program
   );
 
+  const [observationAddress] = await getOrcleAccountAddress(
+    poolAddress,
+    program.programId
+  );
+
+  const [globalConfig] = await getGlobalConfigAddress(program.programId);
+
   const tx = await program.methods
     .withdraw(lp_token_amount, minimum_token_0_amount, minimum_token_1_amount)
     .accounts({
@@ -497,6 +633,9 @@ export async function withdraw(
       vault1Mint: token1,
       lpMint: lpMintAddress,
       memoProgram: new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+      observationState: observationAddress,
+      ammConfig: configAddress,
+      globalConfig,
     })
     .rpc(confirmOptions)
     .catch();
@@ -552,6 +691,8 @@ export async function swap_base_input(
     program.programId
   );
 
+  const [globalConfig] = await getGlobalConfigAddress(program.programId);
+
   const tx = await program.methods
     .swapBaseInput(amount_in, minimum_amount_out)
     .accounts({
@@ -568,6 +709,7 @@ export async function swap_base_input(
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
       observationState: observationAddress,
+      globalConfig,
     })
     .rpc(confirmOptions);
 
@@ -625,6 +767,8 @@ export async function swap_base_output(
     program.programId
   );
 
+  const [globalConfig] = await getGlobalConfigAddress(program.programId);
+
   const tx = await program.methods
     .swapBaseOutput(max_amount_in, amount_out_less_fee)
     .accounts({
@@ -641,6 +785,7 @@ export async function swap_base_output(
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
       observationState: observationAddress,
+      globalConfig,
     })
     .rpc(confirmOptions);
 
@@ -1,5 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
+export const GLOBAL_CONFIG_SEED = Buffer.from(
+  anchor.utils.bytes.utf8.encode("global_config")
+);
 export const AMM_CONFIG_SEED = Buffer.from(
   anchor.utils.bytes.utf8.encode("amm_config")
 );
@@ -53,6 +56,16 @@ export function i32ToBytes(num: number) {
   return new Uint8Array(arr);
 }
 
+export async function getGlobalConfigAddress(
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [GLOBAL_CONFIG_SEED],
+    programId
+  );
+  return [address, bump];
+}
+
 export async function getAmmConfigAddress(
   index: number,
   programId: PublicKey